@@ -0,0 +1,244 @@
+use crate::inventory::weapon_base_id;
+use crate::params::{PARAMS, PARAM_NAMES};
+
+/// `EquipParamWeapon::gem_mount_type` of weapons that take Ashes of War and
+/// affinities.
+const GEM_MOUNT_CHANGEABLE: u8 = 2;
+
+/// Placeholder for `canMountWep_*` flags that no weapon type maps to.
+const NO_WEP_TYPE: u16 = u16::MAX;
+
+/// Weapon types in the order of `EquipParamGem`'s `canMountWep_*` flags.
+/// The shield and torch flags are the first bits of `reserved2_can_mount_wep`.
+/// Types that aren't listed can't take Ashes of War.
+const MOUNT_WEP_TYPES: [u16; 36] = [
+    1,           // Dagger
+    3,           // SwordNormal
+    5,           // SwordLarge
+    7,           // SwordGigantic
+    9,           // SaberNormal
+    11,          // SaberLarge
+    13,          // Katana
+    14,          // SwordDoubleEdge
+    15,          // SwordPierce
+    16,          // RapierHeavy
+    17,          // AxeNormal
+    19,          // AxeLarge
+    21,          // HammerNormal
+    23,          // HammerLarge
+    24,          // Flail
+    25,          // SpearNormal
+    26,          // SpearLarge
+    28,          // SpearHeavy
+    29,          // SpearAxe
+    31,          // Sickle
+    35,          // Knuckle
+    37,          // Claw
+    39,          // Whip
+    41,          // AxhammerLarge
+    50,          // BowSmall
+    51,          // BowNormal
+    53,          // BowLarge
+    55,          // ClossBow
+    56,          // Ballista
+    57,          // Staff
+    61,          // Sorcery
+    NO_WEP_TYPE, // Talisman
+    65,          // ShieldSmall
+    67,          // ShieldNormal
+    69,          // ShieldLarge
+    87,          // Torch
+];
+
+/// What can be done to a weapon, from `EquipParamWeapon` and
+/// `ReinforceParamWeapon`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeaponInfo {
+    pub wep_type: u16,
+    pub max_upgrade: u32,
+    /// Whether the weapon takes Ashes of War and affinities at all.
+    pub gem_mountable: bool,
+    /// Affinity offsets (multiples of 100) that have their own weapon row.
+    pub affinities: Vec<u32>,
+}
+
+impl WeaponInfo {
+    /// Reads the info for a weapon row id. Upgrade and affinity are stripped
+    /// to find the base weapon.
+    pub fn from_params(row_id: u32) -> Option<WeaponInfo> {
+        let (base_id, _) = weapon_base_id(row_id);
+        let base_id = base_id - base_id % 10000;
+
+        let params = PARAMS.read();
+        let weapons = || unsafe { params.get_equip_param_weapon() }.into_iter().flatten();
+
+        let weapon = weapons().find(|p| p.id == base_id as u64)?.param?;
+        let wep_type = weapon.wep_type;
+        let gem_mountable =
+            weapon.gem_mount_type == GEM_MOUNT_CHANGEABLE && !weapon.disable_gem_attr();
+
+        let max_upgrade = unsafe { params.get_reinforce_param_weapon() }
+            .into_iter()
+            .flatten()
+            .find(|p| p.id == weapon.reinforce_type_id as u64)
+            .and_then(|p| p.param)
+            .map(|p| p.max_reinforce_level as u32)
+            .unwrap_or(0);
+
+        let affinities = if gem_mountable {
+            weapons()
+                .map(|p| p.id)
+                .filter(|&id| id >= base_id as u64 && id < base_id as u64 + 10000 && id % 100 == 0)
+                .map(|id| (id - base_id as u64) as u32)
+                .collect()
+        } else {
+            vec![0]
+        };
+
+        Some(WeaponInfo { wep_type, max_upgrade, gem_mountable, affinities })
+    }
+
+    pub fn allows(&self, upgrade: u32, affinity: u32) -> bool {
+        upgrade <= self.max_upgrade && self.affinities.contains(&affinity)
+    }
+}
+
+/// An Ash of War and the weapons and affinities it allows, from
+/// `EquipParamGem`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GemInfo {
+    pub row_id: u32,
+    pub name: String,
+    mount_flags: u64,
+    affinity_flags: u32,
+}
+
+impl GemInfo {
+    /// All the Ashes of War that have a name.
+    pub fn all() -> Vec<GemInfo> {
+        let names = PARAM_NAMES.get("EquipParamGem");
+        let params = PARAMS.read();
+
+        unsafe { params.get_equip_param_gem() }
+            .into_iter()
+            .flatten()
+            .filter_map(|p| {
+                let name = names?.get(&(p.id as usize))?.clone();
+                let gem = p.param?;
+                Some(GemInfo {
+                    row_id: p.id as u32,
+                    name,
+                    mount_flags: mount_flags(
+                        [gem.bitfield4, gem.bitfield5, gem.bitfield6, gem.bitfield7],
+                        gem.reserved2_can_mount_wep,
+                    ),
+                    affinity_flags: affinity_flags([
+                        gem.bitfield0,
+                        gem.bitfield1,
+                        gem.bitfield2,
+                        gem.bitfield3,
+                    ]),
+                })
+            })
+            .collect()
+    }
+
+    pub fn item_id(&self) -> u32 {
+        0x8000_0000 | self.row_id
+    }
+
+    /// Whether the gem can be mounted on a weapon type.
+    pub fn can_mount(&self, wep_type: u16) -> bool {
+        wep_type != NO_WEP_TYPE
+            && MOUNT_WEP_TYPES
+                .iter()
+                .position(|&t| t == wep_type)
+                .is_some_and(|idx| self.mount_flags & (1 << idx) != 0)
+    }
+
+    /// Whether the gem lets the weapon take the given affinity offset.
+    pub fn allows_affinity(&self, affinity: u32) -> bool {
+        let idx = affinity / 100;
+        idx < 24 && self.affinity_flags & (1 << idx) != 0
+    }
+}
+
+/// Packs the `canMountWep_*` flags, which start at bit 1 of the first
+/// bitfield, into consecutive bits.
+fn mount_flags(bitfields: [u8; 4], reserved: [u8; 3]) -> u64 {
+    bitfields
+        .into_iter()
+        .chain(reserved)
+        .enumerate()
+        .fold(0u64, |acc, (i, b)| acc | ((b as u64) << (i * 8)))
+        >> 1
+}
+
+/// Packs the `configurableWepAttr00`..`23` flags, which start at bit 2 of the
+/// first bitfield, into consecutive bits.
+fn affinity_flags(bitfields: [u8; 4]) -> u32 {
+    (u32::from_le_bytes(bitfields) >> 2) & 0x00ff_ffff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_flags() {
+        // Dagger is bit 1 of the first bitfield, Flail bit 7 of the second.
+        let flags = mount_flags([0b10, 0b1000_0000, 0, 0], [0; 3]);
+        assert_eq!(flags, 1 | (1 << 14));
+        assert_eq!(MOUNT_WEP_TYPES[14], 24);
+
+        let gem =
+            GemInfo { row_id: 10000, name: String::new(), mount_flags: flags, affinity_flags: 0 };
+        assert!(gem.can_mount(1));
+        assert!(gem.can_mount(24));
+        assert!(!gem.can_mount(3));
+        assert!(!gem.can_mount(1000));
+    }
+
+    #[test]
+    fn test_mount_shields() {
+        // Small, medium and greatshields are bits 1 to 3 of the first
+        // reserved byte, after the Talisman flag.
+        let shield = GemInfo {
+            row_id: 10000,
+            name: String::new(),
+            mount_flags: mount_flags([0; 4], [0b1110, 0, 0]),
+            affinity_flags: 0,
+        };
+        assert!(shield.can_mount(65));
+        assert!(shield.can_mount(67));
+        assert!(shield.can_mount(69));
+        assert!(!shield.can_mount(3));
+        assert!(!shield.can_mount(87));
+
+        // SwordNormal is bit 2 of the first bitfield.
+        let sword = GemInfo {
+            row_id: 10100,
+            name: String::new(),
+            mount_flags: mount_flags([0b100, 0, 0, 0], [0; 3]),
+            affinity_flags: 0,
+        };
+        assert!(sword.can_mount(3));
+        assert!(!sword.can_mount(65));
+        assert!(!sword.can_mount(67));
+        assert!(!sword.can_mount(69));
+    }
+
+    #[test]
+    fn test_affinity_flags() {
+        // Standard and heavy are bits 2 and 3 of the first bitfield, blood is
+        // bit 5 of the second.
+        let flags = affinity_flags([0b1100, 0b10_0000, 0, 0b11]);
+        let gem =
+            GemInfo { row_id: 10000, name: String::new(), mount_flags: 0, affinity_flags: flags };
+        assert!(gem.allows_affinity(0));
+        assert!(gem.allows_affinity(100));
+        assert!(!gem.allows_affinity(200));
+        assert!(gem.allows_affinity(1100));
+        assert!(!gem.allows_affinity(1200));
+    }
+}
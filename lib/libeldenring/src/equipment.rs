@@ -0,0 +1,119 @@
+use crate::inventory::ItemCategory;
+
+/// Weapon id of an empty hand.
+pub const UNARMED: u32 = 110000;
+
+/// Protector ids of the bare head, chest, arms and legs.
+pub const BARE_ARMOR: [u32; 4] = [10000, 10100, 10200, 10300];
+
+/// Id stored in empty talisman, ammo and spell slots.
+pub const EMPTY_SLOT: u32 = u32::MAX;
+
+/// Equipped item ids in `PlayerGameData`'s `ChrAsm`. Ids are param row ids,
+/// without the item category bits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct EquippedItems {
+    /// Left and right hand weapons, interleaved: L1, R1, L2, R2, L3, R3.
+    pub weapons: [u32; 6],
+    /// Arrow 1, bolt 1, arrow 2, bolt 2.
+    pub ammo: [u32; 4],
+    _unk28: [u32; 2],
+    /// Head, chest, arms, legs.
+    pub armor: [u32; 4],
+    _unk40: u32,
+    pub talismans: [u32; 4],
+}
+
+impl EquippedItems {
+    pub fn left_hand(&self) -> [u32; 3] {
+        [self.weapons[0], self.weapons[2], self.weapons[4]]
+    }
+
+    pub fn right_hand(&self) -> [u32; 3] {
+        [self.weapons[1], self.weapons[3], self.weapons[5]]
+    }
+
+    pub fn set_left_hand(&mut self, weapons: [u32; 3]) {
+        for (i, weapon) in weapons.into_iter().enumerate() {
+            self.weapons[i * 2] = weapon;
+        }
+    }
+
+    pub fn set_right_hand(&mut self, weapons: [u32; 3]) {
+        for (i, weapon) in weapons.into_iter().enumerate() {
+            self.weapons[i * 2 + 1] = weapon;
+        }
+    }
+
+    pub fn arrows(&self) -> [u32; 2] {
+        [self.ammo[0], self.ammo[2]]
+    }
+
+    pub fn bolts(&self) -> [u32; 2] {
+        [self.ammo[1], self.ammo[3]]
+    }
+
+    pub fn set_arrows(&mut self, arrows: [u32; 2]) {
+        self.ammo[0] = arrows[0];
+        self.ammo[2] = arrows[1];
+    }
+
+    pub fn set_bolts(&mut self, bolts: [u32; 2]) {
+        self.ammo[1] = bolts[0];
+        self.ammo[3] = bolts[1];
+    }
+
+    /// Whether an item id, with its category bits, is in one of the slots.
+    pub fn contains(&self, item_id: u32) -> bool {
+        match ItemCategory::from_item_id(item_id) {
+            Some((ItemCategory::Weapon, row_id)) => {
+                self.weapons.contains(&row_id) || self.ammo.contains(&row_id)
+            },
+            Some((ItemCategory::Protector, row_id)) => self.armor.contains(&row_id),
+            Some((ItemCategory::Accessory, row_id)) => self.talismans.contains(&row_id),
+            _ => false,
+        }
+    }
+}
+
+/// A quick item or pouch slot in `EquipItemData`. The item id keeps its
+/// category bits, and the index points into the inventory's item list.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct QuickSlot {
+    pub item_id: u32,
+    pub index: i32,
+}
+
+/// Goods on the quick item bar and in the pouch, from `EquipItemData`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct QuickItems {
+    pub quick_slots: [QuickSlot; 10],
+    pub pouch: [QuickSlot; 6],
+}
+
+impl QuickItems {
+    /// Whether an item id, with its category bits, is in one of the slots.
+    pub fn contains(&self, item_id: u32) -> bool {
+        item_id != EMPTY_SLOT
+            && self.quick_slots.iter().chain(self.pouch.iter()).any(|s| s.item_id == item_id)
+    }
+}
+
+/// A memorized spell slot in `EquipMagicData`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct EquippedSpell {
+    pub spell_id: u32,
+    _unk4: u32,
+}
+
+impl EquippedSpell {
+    pub fn new(spell_id: u32) -> Self {
+        EquippedSpell { spell_id, _unk4: 0 }
+    }
+}
+
+pub type EquippedSpells = [EquippedSpell; 14];
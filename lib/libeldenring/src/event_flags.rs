@@ -0,0 +1,231 @@
+use crate::memedit::PointerChain;
+
+/// Reads and writes event flags through `CSFD4VirtualMemoryFlag`, the game's
+/// event flag manager (also known as `CSEventFlagMan`).
+///
+/// Flags are stored in blocks of `divisor` flags each. The blocks are indexed
+/// by a `std::map`-like tree keyed by `flag_id / divisor`, and the flag itself
+/// is a single bit in the block.
+#[derive(Debug, Clone)]
+pub struct EventFlags {
+    base: usize,
+}
+
+impl EventFlags {
+    pub fn new(virtual_memory_flag: usize) -> Self {
+        EventFlags { base: virtual_memory_flag }
+    }
+
+    /// Reads the state of an event flag. Returns `None` if the flag manager is
+    /// not available or the flag's block does not exist.
+    pub fn get_flag(&self, flag_id: u32) -> Option<bool> {
+        get_flag(&Process, self.base, flag_id)
+    }
+
+    /// Sets or clears an event flag. Returns `None` if the flag manager is not
+    /// available or the flag's block does not exist.
+    pub fn set_flag(&self, flag_id: u32, value: bool) -> Option<()> {
+        set_flag(&Process, self.base, flag_id, value)
+    }
+}
+
+/// Memory access for the flag lookup, so that it can run against a fake
+/// address space in tests.
+trait Memory {
+    fn read<T>(&self, addr: usize) -> Option<T>;
+    fn write<T>(&self, addr: usize, value: T) -> Option<()>;
+}
+
+struct Process;
+
+impl Memory for Process {
+    fn read<T>(&self, addr: usize) -> Option<T> {
+        PointerChain::<T>::new(&[addr]).read()
+    }
+
+    fn write<T>(&self, addr: usize, value: T) -> Option<()> {
+        PointerChain::<T>::new(&[addr]).write(value)
+    }
+}
+
+fn get_flag(mem: &impl Memory, base: usize, flag_id: u32) -> Option<bool> {
+    let (addr, mask) = flag_address(mem, base, flag_id)?;
+    mem.read::<u8>(addr).map(|byte| byte & mask != 0)
+}
+
+fn set_flag(mem: &impl Memory, base: usize, flag_id: u32, value: bool) -> Option<()> {
+    let (addr, mask) = flag_address(mem, base, flag_id)?;
+    let byte = mem.read::<u8>(addr)?;
+    mem.write::<u8>(addr, if value { byte | mask } else { byte & !mask })
+}
+
+/// Splits a flag id into its block id, the byte offset in the block and the
+/// bit mask in that byte. Bits are numbered from the most significant one.
+fn flag_position(flag_id: u32, divisor: u32) -> (u32, usize, u8) {
+    let block = flag_id / divisor;
+    let index = flag_id % divisor;
+
+    (block, (index >> 3) as usize, 1 << (7 - (index & 7)))
+}
+
+fn flag_address(mem: &impl Memory, base: usize, flag_id: u32) -> Option<(usize, u8)> {
+    let vmf = mem.read::<usize>(base)?;
+
+    let divisor = mem.read::<u32>(vmf + 0x1c)?;
+    if divisor == 0 {
+        return None;
+    }
+
+    let (block, offset, mask) = flag_position(flag_id, divisor);
+
+    // Lower bound search in the block tree. The head node doubles as the nil
+    // sentinel.
+    let head = mem.read::<usize>(vmf + 0x38)?;
+    let mut node = mem.read::<usize>(head + 0x8)?;
+    let mut found = head;
+
+    while mem.read::<u8>(node + 0x19)? == 0 {
+        if mem.read::<u32>(node + 0x20)? < block {
+            node = mem.read::<usize>(node + 0x10)?;
+        } else {
+            found = node;
+            node = mem.read::<usize>(node)?;
+        }
+    }
+
+    if found == head || block < mem.read::<u32>(found + 0x20)? {
+        return None;
+    }
+
+    let block_addr = match mem.read::<u32>(found + 0x28)? {
+        1 => {
+            let block_size = mem.read::<u32>(vmf + 0x20)? as usize;
+            let block_idx = mem.read::<u32>(found + 0x30)? as usize;
+            mem.read::<usize>(vmf + 0x28)? + block_size * block_idx
+        },
+        2 => return None,
+        _ => mem.read::<usize>(found + 0x30)?,
+    };
+
+    if block_addr == 0 {
+        return None;
+    }
+
+    Some((block_addr + offset, mask))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    const START: usize = 0x1000;
+
+    /// A flat address space starting at `START`.
+    struct FakeMemory(RefCell<Vec<u8>>);
+
+    impl FakeMemory {
+        fn put<T>(&self, addr: usize, value: T) {
+            self.write(addr, value).unwrap();
+        }
+    }
+
+    impl Memory for FakeMemory {
+        fn read<T>(&self, addr: usize) -> Option<T> {
+            let data = self.0.borrow();
+            let bytes = data.get(addr.checked_sub(START)?..)?;
+            (bytes.len() >= std::mem::size_of::<T>())
+                .then(|| unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+        }
+
+        fn write<T>(&self, addr: usize, value: T) -> Option<()> {
+            let mut data = self.0.borrow_mut();
+            let bytes = data.get_mut(addr.checked_sub(START)?..)?;
+            (bytes.len() >= std::mem::size_of::<T>())
+                .then(|| unsafe { std::ptr::write_unaligned(bytes.as_mut_ptr() as *mut T, value) })
+        }
+    }
+
+    const BASE: usize = START;
+    const VMF: usize = START + 0x100;
+    const HEAD: usize = START + 0x200;
+    const NODE_10: usize = START + 0x300;
+    const NODE_60: usize = START + 0x400;
+    const NODE_40: usize = START + 0x500;
+    const BLOCKS: usize = START + 0x1000;
+    const BLOCK_40: usize = START + 0x2000;
+
+    fn node(mem: &FakeMemory, addr: usize, block: u32, kind: u32, value: usize) {
+        mem.put(addr, HEAD);
+        mem.put(addr + 0x10, HEAD);
+        mem.put(addr + 0x19, 0u8);
+        mem.put(addr + 0x20, block);
+        mem.put(addr + 0x28, kind);
+        mem.put(addr + 0x30, value);
+    }
+
+    /// Blocks 10 and 60 live in the block array, block 40 is stored apart.
+    fn flag_man() -> FakeMemory {
+        let mem = FakeMemory(RefCell::new(vec![0; 0x3000]));
+
+        mem.put(BASE, VMF);
+        mem.put(VMF + 0x1c, 1000u32);
+        mem.put(VMF + 0x20, 125u32);
+        mem.put(VMF + 0x28, BLOCKS);
+        mem.put(VMF + 0x38, HEAD);
+
+        mem.put(HEAD + 0x8, NODE_10);
+        mem.put(HEAD + 0x19, 1u8);
+
+        node(&mem, NODE_10, 10, 1, 0);
+        node(&mem, NODE_60, 60, 1, 1);
+        node(&mem, NODE_40, 40, 0, BLOCK_40);
+        mem.put(NODE_10 + 0x10, NODE_60);
+        mem.put(NODE_60, NODE_40);
+
+        mem
+    }
+
+    #[test]
+    fn test_flag_position() {
+        assert_eq!(flag_position(10000, 1000), (10, 0, 0x80));
+        assert_eq!(flag_position(10007, 1000), (10, 0, 0x01));
+        assert_eq!(flag_position(10008, 1000), (10, 1, 0x80));
+        assert_eq!(flag_position(60123, 1000), (60, 15, 0x10));
+        assert_eq!(flag_position(1045630100, 1000), (1045630, 12, 0x08));
+    }
+
+    #[test]
+    fn test_flag_address() {
+        let mem = flag_man();
+
+        assert_eq!(flag_address(&mem, BASE, 10000), Some((BLOCKS, 0x80)));
+        assert_eq!(flag_address(&mem, BASE, 60123), Some((BLOCKS + 125 + 15, 0x10)));
+        assert_eq!(flag_address(&mem, BASE, 40009), Some((BLOCK_40 + 1, 0x40)));
+
+        assert_eq!(flag_address(&mem, BASE, 5000), None);
+        assert_eq!(flag_address(&mem, BASE, 50000), None);
+        assert_eq!(flag_address(&mem, BASE, 70000), None);
+
+        mem.put(VMF + 0x1c, 0u32);
+        assert_eq!(flag_address(&mem, BASE, 10000), None);
+    }
+
+    #[test]
+    fn test_get_set_flag() {
+        let mem = flag_man();
+
+        assert_eq!(get_flag(&mem, BASE, 60123), Some(false));
+        assert_eq!(set_flag(&mem, BASE, 60123, true), Some(()));
+        assert_eq!(get_flag(&mem, BASE, 60123), Some(true));
+        assert_eq!(get_flag(&mem, BASE, 60122), Some(false));
+        assert_eq!(mem.read::<u8>(BLOCKS + 125 + 15), Some(0x10));
+
+        assert_eq!(set_flag(&mem, BASE, 60122, true), Some(()));
+        assert_eq!(set_flag(&mem, BASE, 60123, false), Some(()));
+        assert_eq!(mem.read::<u8>(BLOCKS + 125 + 15), Some(0x20));
+
+        assert_eq!(set_flag(&mem, BASE, 70000, true), None);
+    }
+}
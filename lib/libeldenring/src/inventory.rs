@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+
+use crate::equipment::{EquippedItems, QuickItems};
+use crate::memedit::PointerChain;
+use crate::params::{PARAMS, PARAM_NAMES};
+
+/// Item id stored in empty inventory slots.
+const EMPTY_ITEM_ID: u32 = u32::MAX;
+
+/// An entry in the player's `EquipInventoryData` item list.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct InventoryItem {
+    pub ga_item_handle: u32,
+    pub item_id: u32,
+    pub quantity: u32,
+    pub sort_id: u32,
+    pub acquisition_index: u32,
+    _unk14: u32,
+}
+
+impl InventoryItem {
+    fn is_empty(&self) -> bool {
+        self.item_id == EMPTY_ITEM_ID || self.item_id == 0 || self.quantity == 0
+    }
+
+    /// Whether the handle points to an instance in the game's GaItem table.
+    /// Weapons, armor and Ashes of War get one per copy; talismans and goods
+    /// use their item id as the handle and own no entry.
+    pub fn has_ga_item(&self) -> bool {
+        matches!(self.ga_item_handle >> 28, 0x8 | 0x9 | 0xc)
+    }
+}
+
+/// Item category, encoded in the top nibble of an item id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ItemCategory {
+    Weapon,
+    Protector,
+    Accessory,
+    Goods,
+    Gem,
+}
+
+impl ItemCategory {
+    pub const ALL: [ItemCategory; 5] = [
+        ItemCategory::Weapon,
+        ItemCategory::Protector,
+        ItemCategory::Accessory,
+        ItemCategory::Goods,
+        ItemCategory::Gem,
+    ];
+
+    /// Splits an item id into its category and its param row id.
+    pub fn from_item_id(item_id: u32) -> Option<(ItemCategory, u32)> {
+        let category = match item_id >> 28 {
+            0x0 => ItemCategory::Weapon,
+            0x1 => ItemCategory::Protector,
+            0x2 => ItemCategory::Accessory,
+            0x4 => ItemCategory::Goods,
+            0x8 => ItemCategory::Gem,
+            _ => return None,
+        };
+        Some((category, item_id & 0x0fff_ffff))
+    }
+
+    /// Item id for the given param row id in this category.
+    pub fn item_id(&self, row_id: u32) -> u32 {
+        let nibble = match self {
+            ItemCategory::Weapon => 0x0,
+            ItemCategory::Protector => 0x1,
+            ItemCategory::Accessory => 0x2,
+            ItemCategory::Goods => 0x4,
+            ItemCategory::Gem => 0x8,
+        };
+        (nibble << 28) | row_id
+    }
+
+    pub fn param_name(&self) -> &'static str {
+        match self {
+            ItemCategory::Weapon => "EquipParamWeapon",
+            ItemCategory::Protector => "EquipParamProtector",
+            ItemCategory::Accessory => "EquipParamAccessory",
+            ItemCategory::Goods => "EquipParamGoods",
+            ItemCategory::Gem => "EquipParamGem",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemCategory::Weapon => "Weapons",
+            ItemCategory::Protector => "Armor",
+            ItemCategory::Accessory => "Talismans",
+            ItemCategory::Goods => "Goods",
+            ItemCategory::Gem => "Ashes of War",
+        }
+    }
+
+    /// Whether items of this category stack in a single slot.
+    pub fn is_stackable(&self) -> bool {
+        matches!(self, ItemCategory::Goods)
+    }
+}
+
+/// Display name of an item id. Weapons not found by their exact id are looked
+/// up by their base id, with the upgrade level appended.
+pub fn item_name(item_id: u32) -> Option<String> {
+    let (category, row_id) = ItemCategory::from_item_id(item_id)?;
+    let names = PARAM_NAMES.get(category.param_name())?;
+
+    if let Some(name) = names.get(&(row_id as usize)) {
+        return Some(name.clone());
+    }
+
+    match category {
+        ItemCategory::Weapon => {
+            let (base_id, upgrade) = weapon_base_id(row_id);
+            names.get(&(base_id as usize)).map(|name| format!("{name} +{upgrade}"))
+        },
+        _ => None,
+    }
+}
+
+/// Splits a weapon row id into the base id (including affinity) and the
+/// upgrade level.
+pub fn weapon_base_id(row_id: u32) -> (u32, u32) {
+    (row_id - row_id % 100, row_id % 100)
+}
+
+/// Maximum quantity of a goods row that can be held, from `EquipParamGoods`'
+/// `max_num`. Returns `Ok(None)` if the row doesn't exist, and an error if the
+/// params can't be read yet.
+pub fn goods_max_num(row_id: u32) -> Result<Option<u32>, String> {
+    let params = PARAMS.read();
+    let mut rows = unsafe { params.get_equip_param_goods() }
+        .ok_or_else(|| "EquipParamGoods isn't loaded yet".to_string())?;
+
+    Ok(rows.find(|p| p.id == row_id as u64).and_then(|p| p.param).map(|p| p.max_num.max(1) as u32))
+}
+
+/// An inventory slot, joined with its param data.
+#[derive(Debug, Clone)]
+pub struct InventoryEntry {
+    pub slot: usize,
+    pub item: InventoryItem,
+    pub category: ItemCategory,
+    pub name: String,
+    pub sort_id: i32,
+    pub max_quantity: Option<u32>,
+}
+
+/// Reads and edits the player's inventory through `GameDataMan`.
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    items: PointerChain<usize>,
+    capacity: PointerChain<u32>,
+    count: PointerChain<u32>,
+    equipped: PointerChain<EquippedItems>,
+    quick_items: PointerChain<QuickItems>,
+}
+
+impl Inventory {
+    pub fn new(
+        items: PointerChain<usize>,
+        capacity: PointerChain<u32>,
+        count: PointerChain<u32>,
+        equipped: PointerChain<EquippedItems>,
+        quick_items: PointerChain<QuickItems>,
+    ) -> Self {
+        Inventory { items, capacity, count, equipped, quick_items }
+    }
+
+    /// Whether an item is equipped, on the quick item bar or in the pouch.
+    /// Items are assumed to be equipped if the equipment can't be read, so
+    /// they are never touched blindly.
+    pub fn is_equipped(&self, item_id: u32) -> bool {
+        let equipped = self.equipped.read().map(|e| e.contains(item_id));
+        let quick_items = self.quick_items.read().map(|q| q.contains(item_id));
+
+        equipped.zip(quick_items).map(|(e, q)| e || q).unwrap_or(true)
+    }
+
+    /// Reads a non-empty slot that isn't equipped, so that it can be edited.
+    fn editable_slot(&self, slot: usize) -> Option<(PointerChain<InventoryItem>, InventoryItem)> {
+        let ptr = self.slot(slot)?;
+        let item = ptr.read().filter(|item| !item.is_empty())?;
+        (!self.is_equipped(item.item_id)).then_some((ptr, item))
+    }
+
+    fn slot(&self, slot: usize) -> Option<PointerChain<InventoryItem>> {
+        let capacity = self.capacity.read()? as usize;
+        if slot >= capacity {
+            return None;
+        }
+
+        let base = self.items.read().filter(|&base| base != 0)?;
+        Some(PointerChain::new(&[base + slot * std::mem::size_of::<InventoryItem>()]))
+    }
+
+    /// Non-empty inventory slots with their slot index.
+    pub fn items(&self) -> Option<Vec<(usize, InventoryItem)>> {
+        let capacity = self.capacity.read()? as usize;
+        let base = self.items.read().filter(|&base| base != 0)?;
+
+        Some(
+            (0..capacity)
+                .filter_map(|slot| {
+                    PointerChain::<InventoryItem>::new(&[
+                        base + slot * std::mem::size_of::<InventoryItem>()
+                    ])
+                    .read()
+                    .filter(|item| !item.is_empty())
+                    .map(|item| (slot, item))
+                })
+                .collect(),
+        )
+    }
+
+    /// Inventory slots joined with their names, sort ids and maximum
+    /// quantities from the equipment params, in the game's sort order.
+    pub fn entries(&self) -> Option<Vec<InventoryEntry>> {
+        let items = self.items()?;
+        let params = param_info();
+
+        let mut entries: Vec<_> = items
+            .into_iter()
+            .filter_map(|(slot, item)| {
+                let (category, row_id) = ItemCategory::from_item_id(item.item_id)?;
+                let (sort_id, max_quantity) =
+                    params.get(&(category, row_id)).copied().unwrap_or((i32::MAX, None));
+                let name = item_name(item.item_id)
+                    .unwrap_or_else(|| format!("Unknown item {:08x}", item.item_id));
+
+                Some(InventoryEntry { slot, item, category, name, sort_id, max_quantity })
+            })
+            .collect();
+
+        entries.sort_by_key(|e| (e.category, e.sort_id, e.item.item_id, e.slot));
+        Some(entries)
+    }
+
+    /// Sets the quantity of the item in a slot. Setting it to zero empties
+    /// the slot. Equipped items are left alone.
+    pub fn set_quantity(&self, slot: usize, quantity: u32) -> Option<()> {
+        if quantity == 0 {
+            return self.remove(slot);
+        }
+
+        let (ptr, mut item) = self.editable_slot(slot)?;
+        item.quantity = quantity;
+        ptr.write(item)
+    }
+
+    /// Replaces the item in a slot with another one of the same kind, like a
+    /// flask with its empty counterpart. The slot keeps its handle, so the
+    /// item count doesn't change. Equipped items are left alone.
+    pub fn set_item(&self, slot: usize, item_id: u32, quantity: u32) -> Option<()> {
+        let (ptr, mut item) = self.editable_slot(slot)?;
+        item.item_id = item_id;
+        item.quantity = quantity;
+        ptr.write(item)
+    }
+
+    /// Empties a slot and updates the inventory's item count. Equipped items
+    /// are left alone, as the equipment would point to an empty slot. Items
+    /// that own a GaItem entry are left alone too: the table isn't mapped,
+    /// so the entry couldn't be released and would leak.
+    pub fn remove(&self, slot: usize) -> Option<()> {
+        let (ptr, mut item) = self.editable_slot(slot)?;
+        if item.has_ga_item() {
+            return None;
+        }
+        let count = self.count.read()?;
+
+        item.ga_item_handle = 0;
+        item.item_id = EMPTY_ITEM_ID;
+        item.quantity = 0;
+        ptr.write(item)?;
+        self.count.write(count.saturating_sub(1))
+    }
+}
+
+/// Sort id and, for goods, the maximum held quantity of every equipment param
+/// row. Other categories don't stack, so they have no maximum.
+fn param_info() -> HashMap<(ItemCategory, u32), (i32, Option<u32>)> {
+    let params = PARAMS.read();
+    let mut info = HashMap::new();
+
+    macro_rules! collect {
+        ($category:expr, $getter:ident) => {
+            if let Some(rows) = unsafe { params.$getter() } {
+                for row in rows {
+                    if let Some(p) = row.param {
+                        info.insert(($category, row.id as u32), (p.sort_id, None));
+                    }
+                }
+            }
+        };
+    }
+
+    collect!(ItemCategory::Weapon, get_equip_param_weapon);
+    collect!(ItemCategory::Protector, get_equip_param_protector);
+    collect!(ItemCategory::Accessory, get_equip_param_accessory);
+    collect!(ItemCategory::Gem, get_equip_param_gem);
+
+    if let Some(rows) = unsafe { params.get_equip_param_goods() } {
+        for row in rows {
+            if let Some(p) = row.param {
+                let max_quantity = Some(p.max_num.max(1) as u32);
+                info.insert((ItemCategory::Goods, row.id as u32), (p.sort_id, max_quantity));
+            }
+        }
+    }
+
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_category() {
+        assert_eq!(ItemCategory::from_item_id(0x400000b4), Some((ItemCategory::Goods, 180)));
+        assert_eq!(ItemCategory::from_item_id(1000000), Some((ItemCategory::Weapon, 1000000)));
+        assert_eq!(ItemCategory::from_item_id(0x100003e8), Some((ItemCategory::Protector, 1000)));
+        assert_eq!(ItemCategory::from_item_id(0xf0000000), None);
+
+        for category in ItemCategory::ALL {
+            let item_id = category.item_id(1234);
+            assert_eq!(ItemCategory::from_item_id(item_id), Some((category, 1234)));
+        }
+    }
+
+    #[test]
+    fn test_equipped_contains() {
+        let mut equipped = EquippedItems::default();
+        equipped.weapons[1] = 1000000;
+        equipped.ammo[0] = 50000000;
+        equipped.armor[0] = 40000;
+        equipped.talismans[2] = 1000;
+
+        assert!(equipped.contains(ItemCategory::Weapon.item_id(1000000)));
+        assert!(equipped.contains(ItemCategory::Weapon.item_id(50000000)));
+        assert!(equipped.contains(ItemCategory::Protector.item_id(40000)));
+        assert!(equipped.contains(ItemCategory::Accessory.item_id(1000)));
+
+        assert!(!equipped.contains(ItemCategory::Weapon.item_id(1000100)));
+        assert!(!equipped.contains(ItemCategory::Accessory.item_id(40000)));
+        assert!(!equipped.contains(ItemCategory::Goods.item_id(1000)));
+    }
+
+    #[test]
+    fn test_quick_items_contains() {
+        let mut quick_items = QuickItems::default();
+        quick_items.quick_slots[0].item_id = ItemCategory::Goods.item_id(1000);
+        quick_items.pouch[5].item_id = ItemCategory::Goods.item_id(180);
+        quick_items.pouch[4].item_id = u32::MAX;
+
+        assert!(quick_items.contains(ItemCategory::Goods.item_id(1000)));
+        assert!(quick_items.contains(ItemCategory::Goods.item_id(180)));
+
+        assert!(!quick_items.contains(ItemCategory::Goods.item_id(1001)));
+        assert!(!quick_items.contains(ItemCategory::Accessory.item_id(1000)));
+        assert!(!quick_items.contains(u32::MAX));
+    }
+
+    #[test]
+    fn test_has_ga_item() {
+        let item = |ga_item_handle| InventoryItem { ga_item_handle, ..Default::default() };
+
+        assert!(item(0x80800012).has_ga_item());
+        assert!(item(0x90800034).has_ga_item());
+        assert!(item(0xc0800056).has_ga_item());
+        assert!(!item(0xa00003e8).has_ga_item());
+        assert!(!item(0xb00000b4).has_ga_item());
+    }
+
+    #[test]
+    fn test_weapon_base_id() {
+        assert_eq!(weapon_base_id(1000000), (1000000, 0));
+        assert_eq!(weapon_base_id(1000125), (1000100, 25));
+    }
+}
@@ -2,16 +2,30 @@ use std::thread;
 use std::time::Duration;
 
 pub mod codegen;
+pub mod equip_params;
+pub mod equipment;
+pub mod event_flags;
+pub mod inventory;
+pub mod map_id;
 pub mod memedit;
+pub mod msg;
 pub mod params;
 pub mod pointers;
+pub mod rune_level;
 pub mod version;
 
 pub mod prelude {
     pub use crate::codegen::*;
+    pub use crate::equip_params::*;
+    pub use crate::equipment::*;
+    pub use crate::event_flags::*;
+    pub use crate::inventory::*;
+    pub use crate::map_id::*;
     pub use crate::memedit::*;
+    pub use crate::msg::*;
     pub use crate::params::*;
     pub use crate::pointers::*;
+    pub use crate::rune_level::*;
     pub use crate::version::*;
     pub use crate::{wait_option, wait_option_thread, ParamStruct, ParamVisitor};
 }
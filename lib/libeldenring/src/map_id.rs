@@ -0,0 +1,197 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Side of an open world tile, in world units.
+const TILE_SIZE: f32 = 256.;
+
+/// Map identifier as stored in the player's map id field, e.g. `m60_42_36_00`
+/// is area 60, block 42, region 36, index 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MapId(pub u32);
+
+impl MapId {
+    pub fn new(area: u8, block: u8, region: u8, index: u8) -> Self {
+        MapId(u32::from_be_bytes([area, block, region, index]))
+    }
+
+    pub fn area(&self) -> u8 {
+        self.0.to_be_bytes()[0]
+    }
+
+    pub fn block(&self) -> u8 {
+        self.0.to_be_bytes()[1]
+    }
+
+    pub fn region(&self) -> u8 {
+        self.0.to_be_bytes()[2]
+    }
+
+    pub fn index(&self) -> u8 {
+        self.0.to_be_bytes()[3]
+    }
+
+    /// Whether the map is an open world tile (the Lands Between or the Land of
+    /// Shadow) rather than a legacy dungeon or an underground area.
+    pub fn is_overworld(&self) -> bool {
+        matches!(self.area(), 60 | 61)
+    }
+
+    /// Converts a position local to this map to global coordinates. Open world
+    /// tiles are laid out on a grid by block (x) and region (z); other maps
+    /// have no offset.
+    pub fn to_global(&self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        if self.is_overworld() {
+            [x + self.block() as f32 * TILE_SIZE, y, z + self.region() as f32 * TILE_SIZE]
+        } else {
+            [x, y, z]
+        }
+    }
+
+    /// Squared distance in tiles to another map: 0 for the same legacy map
+    /// or tile, `None` if the maps are in different areas or are different
+    /// legacy maps.
+    pub fn grid_distance(&self, other: &MapId) -> Option<i32> {
+        if self.area() != other.area() {
+            None
+        } else if self.is_overworld() {
+            let dx = self.block() as i32 - other.block() as i32;
+            let dz = self.region() as i32 - other.region() as i32;
+            Some(dx * dx + dz * dz)
+        } else if self.block() == other.block() && self.region() == other.region() {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the area the map is in, out of `places`: the map of each grace
+    /// and the name of the place the in-game map lists it under. A grace on
+    /// the same map wins; on open world tiles, the grace on the closest tile
+    /// does.
+    pub fn area_name<S>(&self, places: impl IntoIterator<Item = (MapId, S)>) -> Option<S> {
+        places
+            .into_iter()
+            .filter_map(|(map_id, name)| Some((self.grid_distance(&map_id)?, name)))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, name)| name)
+    }
+
+    /// Whether being loaded in `self` means `other` is loaded too. Open world
+    /// tiles are streamed in, so any tile of the same overworld will do.
+    pub fn same_map(&self, other: &MapId) -> bool {
+        if self.is_overworld() {
+            self.area() == other.area()
+        } else {
+            self.0 == other.0
+        }
+    }
+}
+
+impl Display for MapId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [a, b, r, s] = self.0.to_be_bytes();
+        write!(f, "m{a:02}_{b:02}_{r:02}_{s:02}")
+    }
+}
+
+impl FromStr for MapId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .trim()
+            .trim_start_matches(['m', 'M'])
+            .split('_')
+            .map(|p| p.parse::<u8>().map_err(|e| format!("Invalid map id \"{s}\": {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match parts.as_slice() {
+            [a, b, r, i] => Ok(MapId::new(*a, *b, *r, *i)),
+            _ => Err(format!("Invalid map id \"{s}\": expected mAA_BB_CC_DD")),
+        }
+    }
+}
+
+impl From<u32> for MapId {
+    fn from(value: u32) -> Self {
+        MapId(value)
+    }
+}
+
+impl Serialize for MapId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MapId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(MapId::new(60, 42, 36, 0).to_string(), "m60_42_36_00");
+        assert_eq!(MapId::new(10, 0, 0, 0).to_string(), "m10_00_00_00");
+        assert_eq!(MapId(0x3c2a2400).to_string(), "m60_42_36_00");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("m60_42_36_00".parse(), Ok(MapId::new(60, 42, 36, 0)));
+        assert_eq!(" M10_01_00_00 ".parse(), Ok(MapId::new(10, 1, 0, 0)));
+        assert_eq!("11_05_00_00".parse(), Ok(MapId::new(11, 5, 0, 0)));
+
+        let id = MapId::new(61, 44, 41, 0);
+        assert_eq!(id.to_string().parse(), Ok(id));
+    }
+
+    #[test]
+    fn test_from_str_errors() {
+        assert!("m60_42_36".parse::<MapId>().is_err());
+        assert!("m60_42_36_00_00".parse::<MapId>().is_err());
+        assert!("m60_42_x_00".parse::<MapId>().is_err());
+        assert!("m60_42_256_00".parse::<MapId>().is_err());
+        assert!("".parse::<MapId>().is_err());
+    }
+
+    #[test]
+    fn test_to_global() {
+        assert_eq!(MapId::new(60, 42, 36, 0).to_global([10., 5., 20.]), [10762., 5., 9236.]);
+        assert_eq!(MapId::new(61, 44, 41, 0).to_global([0., 0., 0.]), [11264., 0., 10496.]);
+        assert_eq!(MapId::new(10, 0, 0, 0).to_global([10., 5., 20.]), [10., 5., 20.]);
+    }
+
+    #[test]
+    fn test_area_name() {
+        let places = [
+            (MapId::new(10, 0, 0, 0), "Stormveil Castle"),
+            (MapId::new(20, 1, 0, 0), "Enir-Ilim"),
+            (MapId::new(25, 0, 0, 0), "Cathedral of Manus Metyr"),
+            (MapId::new(30, 2, 0, 0), "Stormfoot Catacombs"),
+            (MapId::new(60, 42, 36, 0), "Limgrave"),
+            (MapId::new(60, 36, 49, 0), "Liurnia of the Lakes"),
+        ];
+        let area_name = |map_id: MapId| map_id.area_name(places);
+
+        assert_eq!(area_name(MapId::new(10, 0, 0, 0)), Some("Stormveil Castle"));
+        assert_eq!(area_name(MapId::new(20, 1, 0, 0)), Some("Enir-Ilim"));
+        assert_eq!(area_name(MapId::new(25, 0, 0, 0)), Some("Cathedral of Manus Metyr"));
+        assert_eq!(area_name(MapId::new(30, 2, 0, 0)), Some("Stormfoot Catacombs"));
+        assert_eq!(area_name(MapId::new(60, 43, 37, 0)), Some("Limgrave"));
+        assert_eq!(area_name(MapId::new(60, 36, 48, 0)), Some("Liurnia of the Lakes"));
+
+        // Other legacy maps in the same area and other overworlds don't count.
+        assert_eq!(area_name(MapId::new(20, 0, 0, 0)), None);
+        assert_eq!(area_name(MapId::new(30, 3, 0, 0)), None);
+        assert_eq!(area_name(MapId::new(61, 44, 41, 0)), None);
+    }
+}
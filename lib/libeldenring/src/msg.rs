@@ -0,0 +1,94 @@
+use crate::memedit::PointerChain;
+
+/// FMG category holding the names of places and graces.
+pub const PLACE_NAME: usize = 19;
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct FmgHeader {
+    _unk00: u8,
+    big_endian: u8,
+    version: u8,
+    _unk03: u8,
+    file_size: i32,
+    _unk08: [u8; 4],
+    group_count: i32,
+    string_count: i32,
+    _unk14: i32,
+    string_offsets_offset: i64,
+    _unk20: i64,
+}
+
+/// Reads localized text from the game's `MsgRepository`. Since the game
+/// loads the messages for the language it is running in, names looked up
+/// through here are already localized.
+#[derive(Debug, Clone)]
+pub struct MsgRepository {
+    base: usize,
+}
+
+impl MsgRepository {
+    pub fn new(msg_repository: usize) -> Self {
+        MsgRepository { base: msg_repository }
+    }
+
+    /// Looks up a text entry by FMG category and id. Returns `None` if the
+    /// repository is not loaded yet or the entry does not exist.
+    pub fn get(&self, category: usize, id: i32) -> Option<String> {
+        let fmg: PointerChain<FmgHeader> =
+            PointerChain::new(&[self.base, 0x8, 0x0, category * 8, 0x0]);
+
+        let header = fmg.read()?;
+        if header.big_endian != 0
+            || header.version != 2
+            || !(0x28..0x4000000).contains(&header.file_size)
+        {
+            return None;
+        }
+
+        let fmg_ptr = fmg.eval()? as *const u8;
+
+        // SAFETY
+        // The header was read successfully and looks sane, so the whole file is
+        // in memory right after it.
+        let data = unsafe { std::slice::from_raw_parts(fmg_ptr, header.file_size as usize) };
+
+        fmg_lookup(data, &header, id)
+    }
+}
+
+fn fmg_lookup(data: &[u8], header: &FmgHeader, id: i32) -> Option<String> {
+    let read_i32 = |offset: usize| -> Option<i32> {
+        data.get(offset..offset + 4).map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+    };
+    let read_i64 = |offset: usize| -> Option<i64> {
+        data.get(offset..offset + 8).map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+    };
+
+    let group = (0..header.group_count.max(0) as usize).find_map(|idx| {
+        let offset = 0x28 + idx * 0x10;
+        let offset_index = read_i32(offset)?;
+        let first_id = read_i32(offset + 4)?;
+        let last_id = read_i32(offset + 8)?;
+
+        (first_id..=last_id).contains(&id).then_some(offset_index + id - first_id)
+    })?;
+
+    if !(0..header.string_count).contains(&group) {
+        return None;
+    }
+
+    let string_offset = read_i64(header.string_offsets_offset as usize + group as usize * 8)?;
+    if string_offset <= 0 {
+        return None;
+    }
+
+    let string = data.get(string_offset as usize..)?;
+    let string = string
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&c| c != 0)
+        .collect::<Vec<_>>();
+
+    Some(String::from_utf16_lossy(&string))
+}
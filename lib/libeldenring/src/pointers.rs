@@ -4,7 +4,11 @@ use std::fmt::Display;
 
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
 
+use crate::equipment::{EquippedItems, EquippedSpells};
+use crate::event_flags::EventFlags;
+use crate::inventory::Inventory;
 use crate::memedit::*;
+use crate::msg::MsgRepository;
 use crate::prelude::base_addresses::BaseAddresses;
 use crate::prelude::Version;
 
@@ -34,6 +38,20 @@ pub struct Pointers {
     pub current_target: PointerChain<u64>,
 
     pub character_stats: PointerChain<CharacterStats>,
+    pub origin_class: PointerChain<u8>,
+    pub great_rune_active: Bitflag<u8>,
+    pub crimson_flasks: PointerChain<u8>,
+    pub cerulean_flasks: PointerChain<u8>,
+    // Shadow of the Erdtree only
+    pub scadutree_blessing: Option<PointerChain<u8>>,
+    pub revered_spirit_ash: Option<PointerChain<u8>>,
+    pub inventory: Inventory,
+    pub equipped_items: PointerChain<EquippedItems>,
+    pub equipped_spells: PointerChain<EquippedSpells>,
+    // Current, base max and max
+    pub player_hp: PointerChain<[u32; 3]>,
+    pub player_fp: PointerChain<[u32; 3]>,
+    pub player_sp: PointerChain<[u32; 3]>,
     pub runes: PointerChain<u32>,
     pub igt: PointerChain<usize>,
 
@@ -58,6 +76,9 @@ pub struct Pointers {
     pub warp1: PointerChain<u64>,
     pub warp2: PointerChain<u64>,
 
+    pub event_flags: EventFlags,
+    pub msg_repository: MsgRepository,
+
     pub deathcam: (Bitflag<u8>, Bitflag<u8>, PointerChain<u8>),
 
     // HitIns
@@ -70,6 +91,7 @@ pub struct Pointers {
     pub field_area_direction: Bitflag<u8>,
     pub field_area_altimeter: Bitflag<u8>,
     pub field_area_compass: Bitflag<u8>,
+    pub camera_direction: PointerChain<[f32; 3]>,
 
     // GroupMask
     // pub show_map: Bitflag<u8>,
@@ -147,6 +169,36 @@ pub struct CharacterStats {
     pub runes_tot: i32,
 }
 
+impl CharacterStats {
+    /// Attributes in the order vigor, mind, endurance, strength, dexterity,
+    /// intelligence, faith, arcane.
+    pub fn attributes(&self) -> [i32; 8] {
+        [
+            self.vigor,
+            self.mind,
+            self.endurance,
+            self.strength,
+            self.dexterity,
+            self.intelligence,
+            self.faith,
+            self.arcane,
+        ]
+    }
+
+    pub fn set_attributes(&mut self, attributes: [i32; 8]) {
+        [
+            self.vigor,
+            self.mind,
+            self.endurance,
+            self.strength,
+            self.dexterity,
+            self.intelligence,
+            self.faith,
+            self.arcane,
+        ] = attributes;
+    }
+}
+
 impl Display for CharacterStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "CharacterStats {{ }}")
@@ -173,6 +225,8 @@ impl Pointers {
             lua_warp,
             cs_lua_event_manager,
             current_target,
+            csfd4_virtual_memory_flag,
+            msg_repository,
             ..
         } = base_addresses;
 
@@ -258,6 +312,20 @@ impl Pointers {
             V1_05_0 => bitflag!(0b1; group_mask + 4),
         };
 
+        // PlayerGameData -> EquipInventoryData
+        let equip_inventory_data = 0x5d0;
+
+        let (scadutree_blessing, revered_spirit_ash) = match version {
+            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
+            | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 => {
+                (None, None)
+            },
+            V2_00_0 | V2_00_1 | V2_02_0 | V2_02_3 => (
+                Some(pointer_chain!(game_data_man, 0x8, 0xfc)),
+                Some(pointer_chain!(game_data_man, 0x8, 0xfd)),
+            ),
+        };
+
         let player_ins = match version {
             V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
             | V1_04_1 | V1_05_0 | V1_06_0 => 0x18468,
@@ -302,12 +370,34 @@ impl Pointers {
             torrent_collision: bitflag!(0b1000; world_chr_man, torrent_enemy_ins, 0x18, 0, 0x58, 0xf0),
 
             character_stats: pointer_chain!(game_data_man, 0x8, 0x3c),
+            origin_class: pointer_chain!(game_data_man, 0x8, 0xbf),
+            great_rune_active: bitflag!(0b1; game_data_man, 0x8, 0xff),
+            crimson_flasks: pointer_chain!(game_data_man, 0x8, 0x101),
+            cerulean_flasks: pointer_chain!(game_data_man, 0x8, 0x102),
+            scadutree_blessing,
+            revered_spirit_ash,
+            // GameDataMan -> PlayerGameData -> EquipInventoryData, with the
+            // ChrAsm and the EquipItemData quick items to check against
+            inventory: Inventory::new(
+                pointer_chain!(game_data_man, 0x8, equip_inventory_data + 0x10),
+                pointer_chain!(game_data_man, 0x8, equip_inventory_data + 0x18),
+                pointer_chain!(game_data_man, 0x8, equip_inventory_data + 0x1c),
+                pointer_chain!(game_data_man, 0x8, 0x398 + 0x24),
+                pointer_chain!(game_data_man, 0x8, 0x538, 0x10),
+            ),
+            // GameDataMan -> PlayerGameData -> ChrAsm
+            equipped_items: pointer_chain!(game_data_man, 0x8, 0x398 + 0x24),
+            // GameDataMan -> PlayerGameData -> EquipMagicData
+            equipped_spells: pointer_chain!(game_data_man, 0x8, 0x530, 0x10),
             runes: pointer_chain!(game_data_man, 0x8, 0x6C),
             igt: pointer_chain!(game_data_man, 0xA0),
 
             quitout: pointer_chain!(cs_menu_man_imp, 0x8, 0x5d),
             cursor_show: bitflag!(0b1; cs_menu_man_imp, 0xAC),
             gravity: bitflag!(0b1; world_chr_man, player_ins, 0x190, 0x68, 0x1d3),
+            player_hp: pointer_chain!(world_chr_man, player_ins, 0x190, 0, 0x138),
+            player_fp: pointer_chain!(world_chr_man, player_ins, 0x190, 0, 0x148),
+            player_sp: pointer_chain!(world_chr_man, player_ins, 0x190, 0, 0x154),
             display_stable_pos: bitflag!(0b1; world_chr_man, player_ins,
                 match version {
                     V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 => 0x6FD,
@@ -389,6 +479,8 @@ impl Pointers {
             field_area_direction: bitflag!(0b1; field_area + 0x9),
             field_area_altimeter: bitflag!(0b1; field_area + 0xA),
             field_area_compass: bitflag!(0b1; field_area + 0xB),
+            // Forward row of the chase camera's world matrix.
+            camera_direction: pointer_chain!(field_area, 0x20, 0x18, 0x30),
             weapon_hitbox1: bitflag!(0b1; damage_ctrl, 0xA0),
             weapon_hitbox2: bitflag!(0b1; damage_ctrl, 0xA1),
             weapon_hitbox3: bitflag!(0b1; damage_ctrl, 0xA4),
@@ -403,6 +495,9 @@ impl Pointers {
             warp1: pointer_chain!(cs_lua_event_manager, 0x18),
             warp2: pointer_chain!(cs_lua_event_manager, 0x08),
 
+            event_flags: EventFlags::new(csfd4_virtual_memory_flag),
+            msg_repository: MsgRepository::new(msg_repository),
+
             func_item_spawn,
             func_item_inject,
             func_dbg_action_force: pointer_chain!(base_addresses.func_dbg_action_force + 7),
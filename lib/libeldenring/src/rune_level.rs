@@ -0,0 +1,112 @@
+use crate::params::PARAMS;
+
+/// Attribute points a level 0 character would have. Every starting class has
+/// exactly this many points more than its level.
+const ATTRIBUTE_OFFSET: i32 = 79;
+
+/// A starting class with its base level and attributes, in the order
+/// vigor, mind, endurance, strength, dexterity, intelligence, faith, arcane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StartingClass {
+    pub name: &'static str,
+    pub level: i32,
+    pub attributes: [i32; 8],
+}
+
+/// Starting classes, indexed by the origin class id stored in the save.
+pub const STARTING_CLASSES: [StartingClass; 10] = [
+    StartingClass { name: "Vagabond", level: 9, attributes: [15, 10, 11, 14, 13, 9, 9, 7] },
+    StartingClass { name: "Warrior", level: 8, attributes: [11, 12, 11, 10, 16, 10, 8, 9] },
+    StartingClass { name: "Hero", level: 7, attributes: [14, 9, 12, 16, 9, 7, 8, 11] },
+    StartingClass { name: "Bandit", level: 5, attributes: [10, 11, 10, 9, 13, 9, 8, 14] },
+    StartingClass { name: "Astrologer", level: 6, attributes: [9, 15, 9, 8, 12, 16, 7, 9] },
+    StartingClass { name: "Prophet", level: 7, attributes: [10, 14, 8, 11, 10, 7, 16, 10] },
+    StartingClass { name: "Confessor", level: 10, attributes: [10, 13, 10, 12, 12, 9, 14, 9] },
+    StartingClass { name: "Samurai", level: 9, attributes: [12, 11, 13, 12, 15, 9, 8, 8] },
+    StartingClass { name: "Prisoner", level: 9, attributes: [11, 12, 11, 11, 14, 14, 6, 9] },
+    StartingClass { name: "Wretch", level: 1, attributes: [10, 10, 10, 10, 10, 10, 10, 10] },
+];
+
+impl StartingClass {
+    /// Base level and attributes of a starting class, read from its
+    /// `CharaInitParam` row. Falls back to the built-in table if the params
+    /// can't be read.
+    pub fn from_params(class_id: u8) -> Option<StartingClass> {
+        let class = *STARTING_CLASSES.get(class_id as usize)?;
+        let row_id = 3000 + class_id as u64;
+
+        let params = PARAMS.read();
+        let param = unsafe { params.get_chara_init_param() }
+            .into_iter()
+            .flatten()
+            .find(|p| p.id == row_id)
+            .and_then(|p| p.param);
+
+        Some(match param {
+            Some(p) => StartingClass {
+                name: class.name,
+                level: p.soul_lv as i32,
+                attributes: [
+                    p.base_vit, p.base_wil, p.base_end, p.base_str, p.base_dex, p.base_mag,
+                    p.base_fai, p.base_luc,
+                ]
+                .map(|a| a as i32),
+            },
+            None => class,
+        })
+    }
+}
+
+/// Level of a character with the given attributes. Since every class starts
+/// with the same offset between attribute points and level, this doesn't
+/// depend on the starting class.
+pub fn level_from_attributes(attributes: &[i32; 8]) -> i32 {
+    attributes.iter().sum::<i32>() - ATTRIBUTE_OFFSET
+}
+
+/// Runes needed to go from `level` to `level + 1`.
+pub fn runes_for_next_level(level: i32) -> u32 {
+    let x = (level + 81) as f64;
+    let factor = ((x - 92.) * 0.02).max(0.) + 0.1;
+    (factor * x * x).floor() as u32 + 1
+}
+
+/// Runes needed to go from level `from` to level `to`.
+pub fn runes_between_levels(from: i32, to: i32) -> u64 {
+    (from..to).map(|level| runes_for_next_level(level) as u64).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runes_for_next_level() {
+        assert_eq!(runes_for_next_level(1), 673);
+        assert_eq!(runes_for_next_level(2), 689);
+        assert_eq!(runes_for_next_level(3), 706);
+        assert_eq!(runes_for_next_level(11), 847);
+        assert_eq!(runes_for_next_level(12), 1038);
+        assert_eq!(runes_for_next_level(150), 153680);
+    }
+
+    #[test]
+    fn test_runes_between_levels() {
+        assert_eq!(runes_between_levels(1, 1), 0);
+        assert_eq!(runes_between_levels(1, 4), 673 + 689 + 706);
+    }
+
+    #[test]
+    fn test_starting_classes() {
+        for class in STARTING_CLASSES {
+            assert_eq!(level_from_attributes(&class.attributes), class.level, "{}", class.name);
+        }
+    }
+
+    #[test]
+    fn test_level_from_attributes() {
+        assert_eq!(level_from_attributes(&[40, 20, 25, 18, 12, 9, 9, 7]), 61);
+        assert_eq!(level_from_attributes(&[99; 8]), 713);
+        assert_eq!(level_from_attributes(&[11, 10, 10, 10, 10, 10, 10, 10]), 2);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use hudhook::tracing::error;
@@ -7,21 +8,40 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::Widget;
 use serde::Deserialize;
 
+use crate::remote::{controlled, Controls, RemoteSettings};
+use crate::text_outputs::TextOutput;
 use crate::widgets::action_freeze::action_freeze;
+use crate::widgets::auto_reset::AutoReset;
+use crate::widgets::boss_reset::{BossReset, Refill};
+use crate::widgets::build_presets::BuildPresets;
 use crate::widgets::character_stats::character_stats_edit;
+use crate::widgets::checkpoints::{CheckpointPointers, Checkpoints};
 use crate::widgets::cycle_speed::cycle_speed;
 use crate::widgets::deathcam::deathcam;
+use crate::widgets::event_flags::{event_flag_widget, EventFlagEditor};
 use crate::widgets::flag::flag_widget;
+use crate::widgets::flasks_blessings::FlasksBlessings;
 use crate::widgets::group::group;
-use crate::widgets::item_spawn::ItemSpawner;
+use crate::widgets::inventory::InventoryEditor;
+use crate::widgets::item_kits::ItemKits;
+use crate::widgets::item_spawn::{Favourite, ItemSpawner};
+use crate::widgets::loadouts::{Loadout, Loadouts};
 use crate::widgets::multiflag::multi_flag;
-use crate::widgets::nudge_pos::nudge_position;
+use crate::widgets::noclip::{Noclip, NoclipKeys};
+use crate::widgets::nudge_pos::{nudge_position, FacingNudges};
+use crate::widgets::player_vitals::PlayerVitals;
 use crate::widgets::position::save_position;
+use crate::widgets::position_library::PositionLibrary;
 use crate::widgets::quitout::quitout;
 use crate::widgets::runes::runes;
 use crate::widgets::savefile_manager::savefile_manager;
+use crate::widgets::segment_timer::SegmentTimer;
+use crate::widgets::split_timer::{SplitDef, SplitTimer};
+use crate::widgets::stats::StatsTracker;
 use crate::widgets::target::Target;
-use crate::widgets::warp::Warp;
+use crate::widgets::teleport::Teleport;
+use crate::widgets::trigger::{Trigger, TriggerSpec};
+use crate::widgets::warp::{LocationWarp, Warp};
 
 #[cfg_attr(test, derive(Debug))]
 #[derive(Deserialize)]
@@ -43,6 +63,9 @@ pub(crate) struct Settings {
     pub(crate) disable_update_prompt: bool,
     #[serde(default = "Indicator::default_set")]
     pub(crate) indicators: Vec<Indicator>,
+    #[serde(default)]
+    pub(crate) text_outputs: Vec<TextOutput>,
+    pub(crate) remote: Option<RemoteSettings>,
 }
 
 #[derive(Deserialize, Copy, Clone, Debug)]
@@ -51,6 +74,7 @@ pub(crate) enum Indicator {
     Igt,
     Position,
     GameVersion,
+    PlayerVitals,
     ImguiDebug,
 }
 
@@ -68,6 +92,7 @@ impl TryFrom<String> for Indicator {
             "igt" => Ok(Indicator::Igt),
             "position" => Ok(Indicator::Position),
             "game_version" => Ok(Indicator::GameVersion),
+            "player_vitals" => Ok(Indicator::PlayerVitals),
             "imgui_debug" => Ok(Indicator::ImguiDebug),
             value => Err(format!("Unrecognized indicator: {value}")),
         }
@@ -102,6 +127,20 @@ enum CfgCommand {
     ItemSpawner {
         #[serde(rename = "item_spawner")]
         hotkey_load: PlaceholderOption<Key>,
+        #[serde(default)]
+        favourites: Vec<Favourite>,
+    },
+    ItemKits {
+        #[serde(rename = "item_kits")]
+        _item_kits: bool,
+    },
+    Inventory {
+        #[serde(rename = "inventory")]
+        _inventory: bool,
+        restore: Option<Key>,
+    },
+    Loadouts {
+        loadouts: Vec<Loadout>,
     },
     Flag {
         flag: FlagSpec,
@@ -115,6 +154,15 @@ enum CfgCommand {
         flag: String,
         hotkey: Option<Key>,
     },
+    EventFlag {
+        event_flag: u32,
+        label: String,
+        hotkey: Option<Key>,
+    },
+    EventFlags {
+        #[serde(rename = "event_flags")]
+        _event_flags: bool,
+    },
     MultiFlagUser {
         flags: Vec<FlagSpec>,
         hotkey: Option<Key>,
@@ -124,10 +172,48 @@ enum CfgCommand {
         position: PlaceholderOption<Key>,
         save: Option<Key>,
     },
+    PositionLibrary {
+        #[serde(rename = "position_library")]
+        hotkey_load: PlaceholderOption<Key>,
+    },
+    Checkpoints {
+        #[serde(rename = "checkpoints")]
+        hotkey_save: PlaceholderOption<Key>,
+        #[serde(default)]
+        hotkeys: BTreeMap<String, Key>,
+    },
+    Teleport {
+        #[serde(rename = "teleport")]
+        hotkey: PlaceholderOption<Key>,
+    },
+    Noclip {
+        #[serde(rename = "noclip")]
+        hotkey: PlaceholderOption<Key>,
+        #[serde(default = "default_noclip_speed")]
+        speed: f32,
+        #[serde(default = "default_noclip_boost")]
+        boost_multiplier: f32,
+        forward: Option<Key>,
+        back: Option<Key>,
+        left: Option<Key>,
+        right: Option<Key>,
+        up: Option<Key>,
+        down: Option<Key>,
+        boost: Option<Key>,
+    },
     NudgePosition {
         nudge: f32,
         nudge_up: Option<Key>,
         nudge_down: Option<Key>,
+        nudge_horizontal: Option<f32>,
+        nudge_forward: Option<Key>,
+        nudge_back: Option<Key>,
+        nudge_left: Option<Key>,
+        nudge_right: Option<Key>,
+        #[serde(default = "default_rotate")]
+        rotate: f32,
+        rotate_left: Option<Key>,
+        rotate_right: Option<Key>,
     },
     CycleSpeed {
         #[serde(rename = "cycle_speed")]
@@ -138,6 +224,19 @@ enum CfgCommand {
         #[serde(rename = "character_stats")]
         hotkey_open: PlaceholderOption<Key>,
     },
+    BuildPresets {
+        #[serde(rename = "build_presets")]
+        _build_presets: bool,
+    },
+    FlasksBlessings {
+        #[serde(rename = "flasks_blessings")]
+        _flasks_blessings: bool,
+    },
+    PlayerVitals {
+        #[serde(rename = "player_vitals")]
+        _player_vitals: bool,
+        refill: Option<Key>,
+    },
     Runes {
         #[serde(rename = "runes")]
         amount: u32,
@@ -151,6 +250,40 @@ enum CfgCommand {
         #[serde(rename = "warp")]
         _warp: bool,
     },
+    BossReset {
+        #[serde(rename = "boss_reset")]
+        hotkey: PlaceholderOption<Key>,
+        #[serde(default)]
+        restore: bool,
+    },
+    AutoReset {
+        #[serde(rename = "auto_reset")]
+        hotkey: PlaceholderOption<Key>,
+        #[serde(default)]
+        reapply: Vec<FlagSpec>,
+        boss: Option<String>,
+    },
+    SplitTimer {
+        #[serde(rename = "split_timer")]
+        hotkey: PlaceholderOption<Key>,
+        splits: Vec<SplitDef>,
+        reset: Option<Key>,
+        livesplit: Option<String>,
+    },
+    Stats {
+        #[serde(rename = "stats")]
+        _stats: bool,
+        #[serde(default)]
+        text_files: bool,
+    },
+    SegmentTimer {
+        segment: String,
+        start: Option<Key>,
+        stop: Option<Key>,
+        retry: Option<Key>,
+        #[serde(flatten)]
+        trigger: TriggerSpec,
+    },
     Group {
         #[serde(rename = "group")]
         label: String,
@@ -163,85 +296,372 @@ enum CfgCommand {
 }
 
 impl CfgCommand {
-    fn into_widget(self, settings: &Settings, chains: &Pointers) -> Option<Box<dyn Widget>> {
+    fn into_widget(
+        self,
+        settings: &Settings,
+        chains: &Pointers,
+        controls: &mut Controls,
+    ) -> Option<Box<dyn Widget>> {
         let widget = match self {
-            CfgCommand::Flag { flag, hotkey } => {
-                flag_widget(&flag.label, (flag.getter)(chains).clone(), hotkey)
-            },
-            CfgCommand::MultiFlag { flag, hotkey } => multi_flag(
-                &flag.label,
-                flag.items.iter().map(|flag| flag(chains).clone()).collect(),
-                hotkey,
+            CfgCommand::Flag { flag, hotkey } => controlled(
+                flag_widget(&flag.label, (flag.getter)(chains).clone(), hotkey),
+                controls,
             ),
-            CfgCommand::MultiFlagUser { flags, hotkey, label } => multi_flag(
-                label.as_str(),
-                flags.iter().map(|flag| (flag.getter)(chains).clone()).collect(),
-                hotkey,
+            CfgCommand::MultiFlag { flag, hotkey } => controlled(
+                multi_flag(
+                    &flag.label,
+                    flag.items.iter().map(|flag| flag(chains).clone()).collect(),
+                    hotkey,
+                ),
+                controls,
             ),
-            CfgCommand::SpecialFlag { flag, hotkey } if flag == "deathcam" => deathcam(
-                chains.deathcam.0.clone(),
-                chains.deathcam.1.clone(),
-                chains.deathcam.2.clone(),
-                hotkey,
+            CfgCommand::MultiFlagUser { flags, hotkey, label } => controlled(
+                multi_flag(
+                    label.as_str(),
+                    flags.iter().map(|flag| (flag.getter)(chains).clone()).collect(),
+                    hotkey,
+                ),
+                controls,
             ),
-            CfgCommand::SpecialFlag { flag, hotkey } if flag == "action_freeze" => action_freeze(
-                chains.func_dbg_action_force.clone(),
-                chains.func_dbg_action_force_state_values,
-                hotkey,
+            CfgCommand::SpecialFlag { flag, hotkey } if flag == "deathcam" => controlled(
+                deathcam(
+                    chains.deathcam.0.clone(),
+                    chains.deathcam.1.clone(),
+                    chains.deathcam.2.clone(),
+                    hotkey,
+                ),
+                controls,
+            ),
+            CfgCommand::SpecialFlag { flag, hotkey } if flag == "action_freeze" => controlled(
+                action_freeze(
+                    chains.func_dbg_action_force.clone(),
+                    chains.func_dbg_action_force_state_values,
+                    hotkey,
+                ),
+                controls,
             ),
             CfgCommand::SpecialFlag { flag, hotkey: _ } => {
                 error!("Invalid flag {}", flag);
                 return None;
             },
+            CfgCommand::EventFlag { event_flag, label, hotkey } => controlled(
+                event_flag_widget(&label, chains.event_flags.clone(), event_flag, hotkey),
+                controls,
+            ),
+            CfgCommand::EventFlags { .. } => controlled(
+                EventFlagEditor::new(chains.event_flags.clone(), settings.display),
+                controls,
+            ),
             CfgCommand::SavefileManager { hotkey_load } => {
-                savefile_manager(hotkey_load.into_option(), settings.display)
+                controlled(savefile_manager(hotkey_load.into_option(), settings.display), controls)
             },
-            CfgCommand::ItemSpawner { hotkey_load } => Box::new(ItemSpawner::new(
-                chains.func_item_inject,
-                chains.base_addresses.map_item_man,
-                chains.gravity.clone(),
-                hotkey_load.into_option(),
-                settings.display,
-            )),
-            CfgCommand::Position { position, save } => save_position(
-                chains.global_position.clone(),
-                chains.chunk_position.clone(),
-                chains.torrent_chunk_position.clone(),
-                position.into_option(),
-                save,
+            CfgCommand::ItemSpawner { hotkey_load, favourites } => controlled(
+                ItemSpawner::new(
+                    chains.func_item_inject,
+                    chains.base_addresses.map_item_man,
+                    chains.gravity.clone(),
+                    hotkey_load.into_option(),
+                    settings.display,
+                    favourites,
+                ),
+                controls,
+            ),
+            CfgCommand::ItemKits { .. } => controlled(
+                ItemKits::new(
+                    chains.func_item_inject,
+                    chains.base_addresses.map_item_man,
+                    chains.gravity.clone(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::Inventory { restore, .. } => controlled(
+                InventoryEditor::new(
+                    chains.inventory.clone(),
+                    chains.func_item_inject,
+                    chains.base_addresses.map_item_man,
+                    chains.gravity.clone(),
+                    restore,
+                    settings.display,
+                ),
+                controls,
             ),
-            CfgCommand::NudgePosition { nudge, nudge_up, nudge_down } => nudge_position(
-                chains.global_position.clone(),
-                chains.chunk_position.clone(),
-                chains.torrent_chunk_position.clone(),
+            CfgCommand::Loadouts { loadouts } => controlled(
+                Loadouts::new(
+                    loadouts,
+                    chains.equipped_items.clone(),
+                    chains.equipped_spells.clone(),
+                    chains.inventory.clone(),
+                    chains.func_item_inject,
+                    chains.base_addresses.map_item_man,
+                    chains.gravity.clone(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::Position { position, save } => controlled(
+                save_position(
+                    chains.global_position.clone(),
+                    chains.chunk_position.clone(),
+                    chains.torrent_chunk_position.clone(),
+                    location_warp(chains),
+                    position.into_option(),
+                    save,
+                ),
+                controls,
+            ),
+            CfgCommand::PositionLibrary { hotkey_load } => controlled(
+                PositionLibrary::new(
+                    chains.global_position.clone(),
+                    chains.chunk_position.clone(),
+                    chains.torrent_chunk_position.clone(),
+                    location_warp(chains),
+                    hotkey_load.into_option(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::Checkpoints { hotkey_save, hotkeys } => controlled(
+                Checkpoints::new(
+                    CheckpointPointers {
+                        global_position: chains.global_position.clone(),
+                        hp: chains.player_hp.clone(),
+                        fp: chains.player_fp.clone(),
+                        sp: chains.player_sp.clone(),
+                        runes: chains.runes.clone(),
+                        character_stats: chains.character_stats.clone(),
+                        animation_speed: chains.animation_speed.clone(),
+                        flags: named_flags(chains),
+                    },
+                    location_warp(chains),
+                    hotkeys,
+                    hotkey_save.into_option(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::Teleport { hotkey } => controlled(
+                Teleport::new(
+                    chains.global_position.clone(),
+                    chains.chunk_position.clone(),
+                    chains.torrent_chunk_position.clone(),
+                    location_warp(chains),
+                    hotkey.into_option(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::Noclip {
+                hotkey,
+                speed,
+                boost_multiplier,
+                forward,
+                back,
+                left,
+                right,
+                up,
+                down,
+                boost,
+            } => {
+                let defaults = NoclipKeys::default();
+                let keys = NoclipKeys {
+                    forward: forward.or(defaults.forward),
+                    back: back.or(defaults.back),
+                    left: left.or(defaults.left),
+                    right: right.or(defaults.right),
+                    up: up.or(defaults.up),
+                    down: down.or(defaults.down),
+                    boost: boost.or(defaults.boost),
+                };
+                controlled(
+                    Noclip::new(
+                        chains.gravity.clone(),
+                        chains.collision.clone(),
+                        chains.torrent_gravity.clone(),
+                        chains.torrent_collision.clone(),
+                        chains.chunk_position.clone(),
+                        chains.torrent_chunk_position.clone(),
+                        chains.camera_direction.clone(),
+                        speed,
+                        boost_multiplier,
+                        keys,
+                        hotkey.into_option(),
+                    ),
+                    controls,
+                )
+            },
+            CfgCommand::NudgePosition {
                 nudge,
                 nudge_up,
                 nudge_down,
+                nudge_horizontal,
+                nudge_forward,
+                nudge_back,
+                nudge_left,
+                nudge_right,
+                rotate,
+                rotate_left,
+                rotate_right,
+            } => controlled(
+                nudge_position(
+                    chains.global_position.clone(),
+                    chains.chunk_position.clone(),
+                    chains.torrent_chunk_position.clone(),
+                    nudge,
+                    nudge_up,
+                    nudge_down,
+                    FacingNudges {
+                        step: nudge_horizontal.unwrap_or(nudge),
+                        forward: nudge_forward,
+                        back: nudge_back,
+                        left: nudge_left,
+                        right: nudge_right,
+                        rotate_step: rotate,
+                        rotate_left,
+                        rotate_right,
+                    },
+                ),
+                controls,
             ),
-            CfgCommand::CycleSpeed { cycle_speed: values, hotkey } => cycle_speed(
-                values.as_slice(),
-                [chains.animation_speed.clone(), chains.torrent_animation_speed.clone()],
-                hotkey,
+            CfgCommand::CycleSpeed { cycle_speed: values, hotkey } => {
+                let speed =
+                    [chains.animation_speed.clone(), chains.torrent_animation_speed.clone()];
+                controlled(cycle_speed(values.as_slice(), speed, hotkey), controls)
+            },
+            CfgCommand::CharacterStats { hotkey_open } => controlled(
+                character_stats_edit(
+                    chains.character_stats.clone(),
+                    chains.origin_class.clone(),
+                    hotkey_open.into_option(),
+                    settings.display,
+                ),
+                controls,
             ),
-            CfgCommand::CharacterStats { hotkey_open } => character_stats_edit(
-                chains.character_stats.clone(),
-                hotkey_open.into_option(),
-                settings.display,
+            CfgCommand::BuildPresets { .. } => controlled(
+                BuildPresets::new(chains.character_stats.clone(), settings.display),
+                controls,
             ),
-            CfgCommand::Runes { amount, hotkey } => runes(amount, chains.runes.clone(), hotkey),
-            CfgCommand::Warp { .. } => Box::new(Warp::new(
-                chains.func_warp,
-                chains.warp1.clone(),
-                chains.warp2.clone(),
-                settings.display,
-            )),
-            CfgCommand::Target { hotkey } => {
-                Box::new(Target::new(chains.current_target.clone(), hotkey.into_option()))
+            CfgCommand::FlasksBlessings { .. } => controlled(
+                FlasksBlessings::new(
+                    chains.crimson_flasks.clone(),
+                    chains.cerulean_flasks.clone(),
+                    chains.great_rune_active.clone(),
+                    chains.scadutree_blessing.clone(),
+                    chains.revered_spirit_ash.clone(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::PlayerVitals { refill, .. } => controlled(
+                PlayerVitals::new(
+                    chains.player_hp.clone(),
+                    chains.player_fp.clone(),
+                    chains.player_sp.clone(),
+                    refill,
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::Runes { amount, hotkey } => {
+                controlled(runes(amount, chains.runes.clone(), hotkey), controls)
+            },
+            CfgCommand::Warp { .. } => controlled(
+                Warp::new(
+                    chains.func_warp,
+                    chains.warp1.clone(),
+                    chains.warp2.clone(),
+                    chains.msg_repository.clone(),
+                    chains.event_flags.clone(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::BossReset { hotkey, restore } => controlled(
+                BossReset::new(
+                    chains.event_flags.clone(),
+                    chains.global_position.clone(),
+                    location_warp(chains),
+                    refill(chains),
+                    restore,
+                    hotkey.into_option(),
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::AutoReset { hotkey, reapply, boss } => controlled(
+                AutoReset::new(
+                    chains.global_position.clone(),
+                    chains.chunk_position.clone(),
+                    chains.torrent_chunk_position.clone(),
+                    refill(chains),
+                    chains.no_dead.clone(),
+                    reapply.iter().map(|flag| (flag.getter)(chains).clone()).collect(),
+                    boss,
+                    hotkey.into_option(),
+                ),
+                controls,
+            ),
+            CfgCommand::SplitTimer { hotkey, splits, reset, livesplit } => controlled(
+                SplitTimer::new(
+                    splits,
+                    chains.igt.clone(),
+                    chains.event_flags.clone(),
+                    chains.global_position.clone(),
+                    livesplit,
+                    hotkey.into_option(),
+                    reset,
+                ),
+                controls,
+            ),
+            CfgCommand::Stats { text_files, .. } => controlled(
+                StatsTracker::new(
+                    chains.player_hp.clone(),
+                    chains.global_position.clone(),
+                    chains.quitout.clone(),
+                    text_files,
+                    settings.display,
+                ),
+                controls,
+            ),
+            CfgCommand::SegmentTimer { segment, start, stop, retry, trigger } => {
+                let trigger = match Trigger::try_from(trigger).and_then(Trigger::resolve) {
+                    Ok(trigger) => trigger,
+                    Err(e) => {
+                        error!("Segment \"{segment}\": {e}");
+                        return None;
+                    },
+                };
+
+                controlled(
+                    SegmentTimer::new(
+                        segment,
+                        trigger,
+                        chains.igt.clone(),
+                        chains.event_flags.clone(),
+                        chains.global_position.clone(),
+                        chains.chunk_position.clone(),
+                        chains.torrent_chunk_position.clone(),
+                        location_warp(chains),
+                        start,
+                        stop,
+                        retry,
+                    ),
+                    controls,
+                )
+            },
+            CfgCommand::Target { hotkey } => controlled(
+                Target::new(chains.current_target.clone(), hotkey.into_option()),
+                controls,
+            ),
+            CfgCommand::Quitout { hotkey } => {
+                controlled(quitout(chains.quitout.clone(), hotkey.into_option()), controls)
             },
-            CfgCommand::Quitout { hotkey } => quitout(chains.quitout.clone(), hotkey.into_option()),
             CfgCommand::Group { label, commands } => group(
                 label.as_str(),
-                commands.into_iter().filter_map(|c| c.into_widget(settings, chains)).collect(),
+                commands
+                    .into_iter()
+                    .filter_map(|c| c.into_widget(settings, chains, controls))
+                    .collect(),
                 settings.display,
             ),
         };
@@ -250,6 +670,39 @@ impl CfgCommand {
     }
 }
 
+fn default_noclip_speed() -> f32 {
+    10.
+}
+
+fn default_noclip_boost() -> f32 {
+    4.
+}
+
+fn default_rotate() -> f32 {
+    15.
+}
+
+fn refill(chains: &Pointers) -> Refill {
+    Refill {
+        hp: chains.player_hp.clone(),
+        fp: chains.player_fp.clone(),
+        crimson_flasks: chains.crimson_flasks.clone(),
+        cerulean_flasks: chains.cerulean_flasks.clone(),
+        inventory: chains.inventory.clone(),
+    }
+}
+
+fn location_warp(chains: &Pointers) -> LocationWarp {
+    LocationWarp::new(
+        chains.func_warp,
+        chains.warp1.clone(),
+        chains.warp2.clone(),
+        chains.global_position.clone(),
+        chains.chunk_position.clone(),
+        chains.torrent_chunk_position.clone(),
+    )
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(try_from = "String")]
 pub(crate) struct LevelFilterSerde(LevelFilter);
@@ -278,8 +731,17 @@ impl Config {
             .map_err(|e| format!("TOML config error at {}: {}", e.path(), e.inner()))
     }
 
-    pub(crate) fn make_commands(self, chains: &Pointers) -> Vec<Box<dyn Widget>> {
-        self.commands.into_iter().filter_map(|c| c.into_widget(&self.settings, chains)).collect()
+    /// Builds the widgets, and the handles the remote control API drives
+    /// them through.
+    pub(crate) fn make_commands(self, chains: &Pointers) -> (Vec<Box<dyn Widget>>, Controls) {
+        let mut controls = Vec::new();
+        let widgets = self
+            .commands
+            .into_iter()
+            .filter_map(|c| c.into_widget(&self.settings, chains, &mut controls))
+            .collect();
+
+        (widgets, controls)
     }
 }
 
@@ -294,6 +756,8 @@ impl Default for Config {
                 show_console: false,
                 indicators: Indicator::default_set(),
                 disable_update_prompt: false,
+                text_outputs: Vec::new(),
+                remote: None,
             },
             commands: Vec::new(),
         }
@@ -323,48 +787,61 @@ impl TryFrom<String> for FlagSpec {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        macro_rules! flag_spec {
-            ($x:expr, [ $( ($flag_name:ident, $flag_label:expr), )* ]) => {
-                match $x {
-                    $(stringify!($flag_name) => Ok(FlagSpec::new($flag_label, |c| &c.$flag_name)),)*
-                    e => Err(format!("\"{}\" is not a valid flag specifier", e)),
-                }
-            }
-        }
-        flag_spec!(value.as_str(), [
-            (one_shot, "One shot"),
-            (no_damage, "All no damage"),
-            (no_dead, "No death"),
-            (no_hit, "No hit"),
-            (no_goods_consume, "Inf Consumables"),
-            (no_stamina_consume, "Inf Stamina"),
-            (no_fp_consume, "Inf Focus"),
-            (no_ashes_of_war_fp_consume, "Inf Focus (AoW)"),
-            (no_arrows_consume, "Inf arrows"),
-            (no_attack, "No attack"),
-            (no_move, "No move"),
-            (no_update_ai, "No update AI"),
-            (gravity, "No Gravity"),
-            (torrent_gravity, "No Gravity (Torrent)"),
-            (collision, "No Collision"),
-            (torrent_collision, "No Collision (Torrent)"),
-            (display_stable_pos, "Show stable pos"),
-            (weapon_hitbox1, "Weapon hitbox #1"),
-            (weapon_hitbox2, "Weapon hitbox #2"),
-            (weapon_hitbox3, "Weapon hitbox #3"),
-            (hitbox_high, "High world hitbox"),
-            (hitbox_low, "Low world hitbox"),
-            (hitbox_f, "Walls hitbox"),
-            (hitbox_character, "Character hitbox"),
-            (field_area_direction, "Direction HUD"),
-            (field_area_altimeter, "Altimeter HUD"),
-            (field_area_compass, "Compass HUD"),
-            // (show_map, "Show/hide map"),
-            (show_chr, "Show/hide character"),
-        ])
+        FLAG_SPECS
+            .iter()
+            .find(|(name, ..)| *name == value)
+            .map(|(_, label, getter)| FlagSpec::new(label, *getter))
+            .ok_or_else(|| format!("\"{}\" is not a valid flag specifier", value))
     }
 }
 
+macro_rules! flag_specs {
+    ($( ($flag_name:ident, $flag_label:expr), )*) => {
+        /// Config name, label and getter of every flag.
+        const FLAG_SPECS: &[(&str, &str, fn(&Pointers) -> &Bitflag<u8>)] = &[
+            $((stringify!($flag_name), $flag_label, |c| &c.$flag_name),)*
+        ];
+    }
+}
+
+flag_specs!(
+    (one_shot, "One shot"),
+    (no_damage, "All no damage"),
+    (no_dead, "No death"),
+    (no_hit, "No hit"),
+    (no_goods_consume, "Inf Consumables"),
+    (no_stamina_consume, "Inf Stamina"),
+    (no_fp_consume, "Inf Focus"),
+    (no_ashes_of_war_fp_consume, "Inf Focus (AoW)"),
+    (no_arrows_consume, "Inf arrows"),
+    (no_attack, "No attack"),
+    (no_move, "No move"),
+    (no_update_ai, "No update AI"),
+    (gravity, "No Gravity"),
+    (torrent_gravity, "No Gravity (Torrent)"),
+    (collision, "No Collision"),
+    (torrent_collision, "No Collision (Torrent)"),
+    (display_stable_pos, "Show stable pos"),
+    (weapon_hitbox1, "Weapon hitbox #1"),
+    (weapon_hitbox2, "Weapon hitbox #2"),
+    (weapon_hitbox3, "Weapon hitbox #3"),
+    (hitbox_high, "High world hitbox"),
+    (hitbox_low, "Low world hitbox"),
+    (hitbox_f, "Walls hitbox"),
+    (hitbox_character, "Character hitbox"),
+    (field_area_direction, "Direction HUD"),
+    (field_area_altimeter, "Altimeter HUD"),
+    (field_area_compass, "Compass HUD"),
+    // (show_map, "Show/hide map"),
+    (show_chr, "Show/hide character"),
+    (great_rune_active, "Great Rune active"),
+);
+
+/// Every flag by its config name, for widgets that snapshot them.
+pub(crate) fn named_flags(chains: &Pointers) -> Vec<(&'static str, Bitflag<u8>)> {
+    FLAG_SPECS.iter().map(|(name, _, getter)| (*name, getter(chains).clone())).collect()
+}
+
 #[derive(Deserialize)]
 #[serde(try_from = "String")]
 struct MultiFlagSpec {
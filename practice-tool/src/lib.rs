@@ -15,7 +15,10 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 mod config;
+mod livesplit;
 mod practice_tool;
+mod remote;
+mod text_outputs;
 mod update;
 mod widgets;
 
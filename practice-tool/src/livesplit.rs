@@ -0,0 +1,148 @@
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use hudhook::tracing::{error, info};
+use practice_tool_core::crossbeam_channel::{unbounded, Receiver, Sender};
+
+use crate::util::Igt;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Client for LiveSplit Server's TCP text protocol.
+///
+/// Commands are sent from a background thread, so a missing or slow server
+/// never stalls the render loop. Commands issued while the server can't be
+/// reached are dropped.
+pub(crate) struct LiveSplit {
+    tx: Sender<String>,
+}
+
+impl LiveSplit {
+    pub(crate) fn new(address: String) -> Self {
+        let (tx, rx) = unbounded();
+        thread::spawn(move || run(address, rx));
+        LiveSplit { tx }
+    }
+
+    fn send(&self, command: String) {
+        self.tx.send(command).ok();
+    }
+
+    pub(crate) fn start_timer(&self) {
+        self.send("starttimer".to_string());
+    }
+
+    pub(crate) fn split(&self) {
+        self.send("split".to_string());
+    }
+
+    pub(crate) fn reset(&self) {
+        self.send("reset".to_string());
+    }
+
+    /// Stops LiveSplit's game time from advancing on its own, so it only
+    /// follows the values sent with [`LiveSplit::set_game_time`].
+    pub(crate) fn pause_game_time(&self) {
+        self.send("pausegametime".to_string());
+    }
+
+    pub(crate) fn set_game_time(&self, time: Duration) {
+        self.send(format!("setgametime {}", Igt::from(time)));
+    }
+}
+
+fn connect(address: &str) -> std::io::Result<TcpStream> {
+    let addr = address.to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "address didn't resolve")
+    })?;
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+    Ok(stream)
+}
+
+fn run(address: String, rx: Receiver<String>) {
+    let mut stream: Option<TcpStream> = None;
+    let mut last_attempt: Option<Instant> = None;
+    let mut reported = false;
+
+    for command in rx {
+        if stream.is_none()
+            && last_attempt.map(|t| t.elapsed() >= RECONNECT_INTERVAL).unwrap_or(true)
+        {
+            last_attempt = Some(Instant::now());
+            match connect(&address) {
+                Ok(s) => {
+                    info!("Connected to LiveSplit Server at {address}");
+                    stream = Some(s);
+                    reported = false;
+                },
+                Err(e) if !reported => {
+                    error!("Couldn't connect to LiveSplit Server at {address}: {e}");
+                    reported = true;
+                },
+                Err(_) => {},
+            }
+        }
+
+        let Some(s) = stream.as_mut() else {
+            continue;
+        };
+
+        if let Err(e) = s.write_all(format!("{command}\r\n").as_bytes()) {
+            error!("Lost connection to LiveSplit Server: {e}");
+            stream = None;
+            last_attempt = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_mock_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let livesplit = LiveSplit::new(listener.local_addr().unwrap().to_string());
+
+        livesplit.start_timer();
+        livesplit.pause_game_time();
+        livesplit.set_game_time(Duration::from_millis(61_500));
+        livesplit.split();
+        livesplit.reset();
+
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let lines = BufReader::new(stream).lines().take(5).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(lines, [
+            "starttimer",
+            "pausegametime",
+            "setgametime 00:01:01.50",
+            "split",
+            "reset"
+        ]);
+    }
+
+    #[test]
+    fn test_server_unavailable() {
+        let address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().to_string()
+        };
+
+        // Nothing is listening, so commands are dropped without blocking.
+        let livesplit = LiveSplit::new(address);
+        let start = Instant::now();
+        livesplit.start_timer();
+        livesplit.split();
+        assert!(start.elapsed() < CONNECT_TIMEOUT);
+    }
+}
@@ -13,7 +13,9 @@ use practice_tool_core::crossbeam_channel::{self, Receiver, Sender};
 use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
 use tracing_subscriber::prelude::*;
 
-use crate::config::{Config, Indicator, Settings};
+use crate::config::{named_flags, Config, Indicator, Settings};
+use crate::remote::Remote;
+use crate::text_outputs::TextOutputs;
 use crate::update::Update;
 use crate::util;
 
@@ -41,6 +43,8 @@ pub(crate) struct PracticeTool {
     pointers: Pointers,
     version_label: String,
     widgets: Vec<Box<dyn Widget>>,
+    text_outputs: TextOutputs,
+    remote: Remote,
 
     log: Vec<(Instant, String)>,
     log_rx: Receiver<String>,
@@ -52,6 +56,7 @@ pub(crate) struct PracticeTool {
 
     position_bufs: [String; 4],
     igt_buf: String,
+    vitals_buf: String,
 }
 
 impl PracticeTool {
@@ -166,7 +171,9 @@ impl PracticeTool {
             format!("Game Ver {}.{:02}.{}", maj, min, patch)
         };
         let settings = config.settings.clone();
-        let widgets = config.make_commands(&pointers);
+        let (widgets, controls) = config.make_commands(&pointers);
+        let text_outputs = TextOutputs::new(settings.text_outputs.clone(), named_flags(&pointers));
+        let remote = Remote::new(settings.remote.as_ref(), controls);
         let (log_tx, log_rx) = crossbeam_channel::unbounded();
         info!("Practice tool initialized");
 
@@ -175,6 +182,8 @@ impl PracticeTool {
             pointers,
             version_label,
             widgets,
+            text_outputs,
+            remote,
             log: Vec::new(),
             log_rx,
             log_tx,
@@ -183,6 +192,7 @@ impl PracticeTool {
             config_err,
             position_bufs: Default::default(),
             igt_buf: Default::default(),
+            vitals_buf: Default::default(),
             update_available,
         }
     }
@@ -377,11 +387,8 @@ impl PracticeTool {
                                 self.pointers.global_position.read(),
                                 self.pointers.global_position.read_map_id(),
                             ) {
-                                let (a, b, r, s) =
-                                    ((m >> 24) & 0xff, (m >> 16) & 0xff, (m >> 8) & 0xff, m & 0xff);
                                 self.position_bufs.iter_mut().for_each(String::clear);
-                                write!(self.position_bufs[0], "m{a:02x}_{b:02x}_{r:02x}_{s:02x}")
-                                    .ok();
+                                write!(self.position_bufs[0], "{}", MapId(m)).ok();
                                 write!(self.position_bufs[1], "{x:.2}").ok();
                                 write!(self.position_bufs[2], "{y:.2}").ok();
                                 write!(self.position_bufs[3], "{z:.2}").ok();
@@ -406,18 +413,28 @@ impl PracticeTool {
                         },
                         Indicator::Igt => {
                             if let Some(igt) = self.pointers.igt.read() {
-                                let millis = (igt % 1000) / 10;
-                                let total_seconds = igt / 1000;
-                                let seconds = total_seconds % 60;
-                                let minutes = total_seconds / 60 % 60;
-                                let hours = total_seconds / 3600;
                                 self.igt_buf.clear();
+                                write!(self.igt_buf, "IGT {}", util::Igt(igt)).ok();
+                                ui.text(&self.igt_buf);
+                            }
+                        },
+                        Indicator::PlayerVitals => {
+                            if let (
+                                Some([hp, _, max_hp]),
+                                Some([fp, _, max_fp]),
+                                Some([sp, _, max_sp]),
+                            ) = (
+                                self.pointers.player_hp.read(),
+                                self.pointers.player_fp.read(),
+                                self.pointers.player_sp.read(),
+                            ) {
+                                self.vitals_buf.clear();
                                 write!(
-                                    self.igt_buf,
-                                    "IGT {hours:02}:{minutes:02}:{seconds:02}.{millis:02}",
+                                    self.vitals_buf,
+                                    "HP {hp}/{max_hp} FP {fp}/{max_fp} SP {sp}/{max_sp}",
                                 )
                                 .ok();
-                                ui.text(&self.igt_buf);
+                                ui.text(&self.vitals_buf);
                             }
                         },
                         Indicator::ImguiDebug => {
@@ -545,6 +562,9 @@ impl ImguiRenderLoop for PracticeTool {
             w.log(self.log_tx.clone());
         }
 
+        self.text_outputs.update(&self.pointers, &self.log_tx);
+        self.remote.update(&self.pointers, &self.log_tx);
+
         let now = Instant::now();
         self.log.extend(self.log_rx.try_iter().inspect(|log| info!("{}", log)).map(|l| (now, l)));
         self.log.retain(|(tm, _)| tm.elapsed() < std::time::Duration::from_secs(5));
@@ -0,0 +1,843 @@
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use hudhook::tracing::{error, info};
+use libeldenring::prelude::*;
+use parking_lot::{Mutex, MutexGuard};
+use practice_tool_core::crossbeam_channel::{self, Receiver, Sender};
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::flag::{Flag, FlagWidget};
+use practice_tool_core::widgets::nudge_position::NudgePositionStorage;
+use practice_tool_core::widgets::store_value::{ReadWrite, StoreValue};
+use practice_tool_core::widgets::Widget;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::Header;
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::{Role, WebSocketConfig};
+use tungstenite::{Message, WebSocket};
+
+use crate::widgets::stats;
+use crate::widgets::warp::area_name;
+
+const MAX_BODY: usize = 64 * 1024;
+/// How long a request waits for the render thread to pick it up.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `[settings.remote]`. The server only listens on localhost, and every
+/// request has to carry the token.
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct RemoteSettings {
+    #[serde(default = "default_port")]
+    pub(crate) port: u16,
+    pub(crate) token: String,
+}
+
+fn default_port() -> u16 {
+    8766
+}
+
+/// Remote side of a config command: the actions it accepts, equivalent to
+/// its hotkeys and buttons, and the state it reports. Controls only run on
+/// the render thread.
+pub(crate) trait Control: Send + Sync {
+    fn label(&self) -> String;
+    fn actions(&self) -> &'static [&'static str];
+    fn run(&mut self, action: &str, arg: &Value) -> Result<(), String>;
+
+    fn state(&mut self) -> Value {
+        Value::Null
+    }
+}
+
+/// The controls of all config commands, in config order.
+pub(crate) type Controls = Vec<Box<dyn Control>>;
+
+/// A widget shared between the overlay and the remote control API, so that
+/// remote actions run on the same instance the overlay renders.
+pub(crate) struct Shared<T>(Arc<Mutex<T>>);
+
+impl<T> Shared<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Shared(Arc::new(Mutex::new(value)))
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        self.0.lock()
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Arc::clone(&self.0))
+    }
+}
+
+impl<W: Widget> Widget for Shared<W> {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.lock().render(ui);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.lock().render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.lock().interact(ui);
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.lock().log(tx);
+    }
+}
+
+impl<C: Control> Control for Shared<C> {
+    fn label(&self) -> String {
+        self.lock().label()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        self.lock().actions()
+    }
+
+    fn run(&mut self, action: &str, arg: &Value) -> Result<(), String> {
+        self.lock().run(action, arg)
+    }
+
+    fn state(&mut self) -> Value {
+        self.lock().state()
+    }
+}
+
+impl<F: Flag> Flag for Shared<F> {
+    fn set(&mut self, value: bool) {
+        self.lock().set(value);
+    }
+
+    fn get(&self) -> Option<bool> {
+        self.lock().get()
+    }
+}
+
+impl<P: NudgePositionStorage> NudgePositionStorage for Shared<P> {
+    fn nudge_up(&mut self) {
+        self.lock().nudge_up();
+    }
+
+    fn nudge_down(&mut self) {
+        self.lock().nudge_down();
+    }
+}
+
+/// Boxes a widget for the overlay and registers it with the remote control
+/// API.
+pub(crate) fn controlled<W: Widget + Control + 'static>(
+    widget: W,
+    controls: &mut Controls,
+) -> Box<dyn Widget> {
+    let widget = Shared::new(widget);
+    controls.push(Box::new(widget.clone()));
+    Box::new(widget)
+}
+
+/// A core flag widget. Remote actions set the flag the widget renders.
+pub(crate) struct FlagCommand<F: Flag> {
+    widget: FlagWidget<Shared<F>>,
+    flag: Shared<F>,
+    label: String,
+}
+
+impl<F: Flag> FlagCommand<F> {
+    pub(crate) fn new(label: &str, flag: F, key: Option<Key>) -> Self {
+        let flag = Shared::new(flag);
+        FlagCommand {
+            widget: FlagWidget::new(label, flag.clone(), key),
+            flag,
+            label: label.to_string(),
+        }
+    }
+}
+
+impl<F: Flag> Widget for FlagCommand<F> {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.widget.render(ui);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.widget.render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.widget.interact(ui);
+    }
+}
+
+impl<F: Flag> Control for FlagCommand<F> {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["toggle", "on", "off"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        let value = match action {
+            "on" => true,
+            "off" => false,
+            _ => !self.flag.get().ok_or("The flag can't be read right now")?,
+        };
+        self.flag.set(value);
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({ "enabled": self.flag.get() })
+    }
+}
+
+/// A core store value widget. The `trigger` action writes the value, the
+/// same as the widget's button and hotkey.
+pub(crate) struct ValueCommand<R: ReadWrite> {
+    widget: StoreValue<SharedValue<R>>,
+    value: Shared<R>,
+}
+
+/// Store value over a shared value. The label is copied out on every read so
+/// it can be borrowed without holding the lock.
+struct SharedValue<R> {
+    value: Shared<R>,
+    label: String,
+}
+
+impl<R: ReadWrite> ReadWrite for SharedValue<R> {
+    fn read(&mut self) -> bool {
+        let mut value = self.value.lock();
+        let read = value.read();
+        self.label.clear();
+        self.label.push_str(value.label());
+        read
+    }
+
+    fn write(&mut self) {
+        self.value.lock().write();
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl<R: ReadWrite> ValueCommand<R> {
+    pub(crate) fn new(value: R, key: Option<Key>) -> Self {
+        let value = Shared::new(value);
+        let store = SharedValue { value: value.clone(), label: String::new() };
+        ValueCommand { widget: StoreValue::new(store, key), value }
+    }
+}
+
+impl<R: ReadWrite> Widget for ValueCommand<R> {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.widget.render(ui);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.widget.render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.widget.interact(ui);
+    }
+}
+
+impl<R: ReadWrite> Control for ValueCommand<R> {
+    fn label(&self) -> String {
+        let mut value = self.value.lock();
+        value.read();
+        value.label().to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["trigger"]
+    }
+
+    fn run(&mut self, _: &str, _: &Value) -> Result<(), String> {
+        let mut value = self.value.lock();
+        if !value.read() {
+            return Err("The value can't be read right now".to_string());
+        }
+        value.write();
+        Ok(())
+    }
+}
+
+/// Reads an action argument, e.g. `name` from `{ "name": "Margit" }`.
+/// Optional arguments are read as an `Option`.
+pub(crate) fn arg<'a, T: Deserialize<'a>>(args: &'a Value, key: &str) -> Result<T, String> {
+    let value = args.get(key).unwrap_or(&Value::Null);
+    T::deserialize(value).map_err(|e| match value {
+        Value::Null => format!("Missing argument \"{key}\""),
+        _ => format!("Invalid argument \"{key}\": {e}"),
+    })
+}
+
+/// Index of the entry picked by the `name` argument, for widgets that act on
+/// a selection from a list.
+pub(crate) fn select<T>(
+    entries: &[T],
+    name: impl Fn(&T) -> &str,
+    args: &Value,
+) -> Result<usize, String> {
+    let wanted: &str = arg(args, "name")?;
+    entries.iter().position(|e| name(e) == wanted).ok_or_else(|| format!("No entry \"{wanted}\""))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    body: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Response { status, body: json!({ "error": message.into() }) }
+    }
+}
+
+/// A request waiting for the render thread.
+struct Call {
+    request: Request,
+    reply: Sender<Response>,
+}
+
+/// Routes requests to the config commands.
+struct Registry {
+    controls: Controls,
+}
+
+impl Registry {
+    fn describe(&mut self, id: usize) -> Value {
+        let control = &mut self.controls[id];
+
+        json!({
+            "id": id,
+            "label": control.label(),
+            "actions": control.actions(),
+            "state": control.state(),
+        })
+    }
+
+    fn handle(&mut self, request: &Request, state: impl FnOnce() -> Value) -> Response {
+        let segments = request.path.trim_matches('/').split('/').collect::<Vec<_>>();
+        let count = self.controls.len();
+        let id = |id: &str| id.parse::<usize>().ok().filter(|id| *id < count);
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["state"]) => Response::ok(state()),
+            ("GET", ["widgets"]) => {
+                Response::ok((0..self.controls.len()).map(|id| self.describe(id)).collect())
+            },
+            ("GET", ["widgets", widget]) => match id(widget) {
+                Some(id) => Response::ok(self.describe(id)),
+                None => Response::error(404, format!("No widget {widget}")),
+            },
+            ("POST", ["widgets", widget, action]) => {
+                let Some(id) = id(widget) else {
+                    return Response::error(404, format!("No widget {widget}"));
+                };
+                let control = &mut self.controls[id];
+                if !control.actions().iter().any(|a| a == action) {
+                    return Response::error(404, format!("Widget {id} has no action \"{action}\""));
+                }
+
+                match control.run(action, &request.body) {
+                    Ok(()) => Response::ok(self.describe(id)),
+                    Err(e) => Response::error(409, e),
+                }
+            },
+            (_, ["state"] | ["widgets", ..]) => Response::error(405, "Method not allowed"),
+            _ => Response::error(404, format!("No route {}", request.path)),
+        }
+    }
+}
+
+/// Player state for `GET /state`. Values that can't be read are `null`.
+fn live_state(pointers: &Pointers) -> Value {
+    let map_id = pointers.global_position.read_map_id().map(MapId);
+    let stat =
+        |value: Option<[u32; 3]>| value.map(|[cur, _, max]| json!({ "current": cur, "max": max }));
+
+    json!({
+        "igt": pointers.igt.read(),
+        "map_id": map_id.map(|m| m.to_string()),
+        "area": map_id.and_then(|m| area_name(m, &pointers.msg_repository)),
+        "position": pointers.global_position.read().map(|[x, y, z, ..]| [x, y, z]),
+        "hp": stat(pointers.player_hp.read()),
+        "fp": stat(pointers.player_fp.read()),
+        "sp": stat(pointers.player_sp.read()),
+        "deaths": stats::deaths(),
+        "attempts": stats::attempts(),
+    })
+}
+
+/// Listens on localhost and hands requests over to the render thread.
+struct Server {
+    port: u16,
+    calls: Receiver<Call>,
+}
+
+impl Server {
+    fn bind(port: u16, token: String) -> Result<Self, String> {
+        let server = tiny_http::Server::http(("127.0.0.1", port)).map_err(|e| e.to_string())?;
+        let port = server.server_addr().to_ip().map(|addr| addr.port()).unwrap_or(port);
+        let token: Arc<str> = token.into();
+        let (tx, calls) = crossbeam_channel::unbounded();
+
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let (token, tx) = (Arc::clone(&token), tx.clone());
+                thread::spawn(move || {
+                    if let Err(e) = serve(request, &token, &tx) {
+                        info!("Remote control connection closed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Server { port, calls })
+    }
+}
+
+/// The remote control API, answered from the render thread.
+pub(crate) struct Remote {
+    server: Option<Server>,
+    registry: Registry,
+    logs: Vec<String>,
+}
+
+impl Remote {
+    pub(crate) fn new(settings: Option<&RemoteSettings>, controls: Controls) -> Self {
+        let mut logs = Vec::new();
+
+        let server = settings.and_then(|settings| {
+            let server = if settings.token.is_empty() {
+                Err("a token is required".to_string())
+            } else {
+                Server::bind(settings.port, settings.token.clone())
+            };
+
+            match server {
+                Ok(server) => {
+                    info!("Remote control listening on 127.0.0.1:{}", server.port);
+                    Some(server)
+                },
+                Err(e) => {
+                    let e = format!("Couldn't start remote control: {e}");
+                    error!("{e}");
+                    logs.push(e);
+                    None
+                },
+            }
+        });
+
+        Remote { server, registry: Registry { controls }, logs }
+    }
+
+    pub(crate) fn update(&mut self, pointers: &Pointers, tx: &Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+
+        let Some(server) = &self.server else {
+            return;
+        };
+
+        for call in server.calls.try_iter() {
+            let response = self.registry.handle(&call.request, || live_state(pointers));
+            call.reply.send(response).ok();
+        }
+    }
+}
+
+/// Whether a request carries the token, in an `Authorization: Bearer` header
+/// or in the query string for clients that can't set headers, such as
+/// browser WebSockets.
+fn is_authorized(request: &tiny_http::Request, query: &str, token: &str) -> bool {
+    header(request, "Authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value == token)
+        .unwrap_or(false)
+        || form_urlencoded::parse(query.as_bytes()).any(|(k, v)| k == "token" && v == token)
+}
+
+fn header<'a>(request: &'a tiny_http::Request, name: &'static str) -> Option<&'a str> {
+    request.headers().iter().find(|h| h.field.equiv(name)).map(|h| h.value.as_str())
+}
+
+fn read_body(request: &mut tiny_http::Request) -> Result<Value, String> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| e.to_string())?;
+
+    if body.len() > MAX_BODY {
+        Err("Request body too large".to_string())
+    } else if body.iter().all(u8::is_ascii_whitespace) {
+        Ok(Value::Null)
+    } else {
+        serde_json::from_slice(&body).map_err(|e| format!("Invalid JSON body: {e}"))
+    }
+}
+
+fn respond(request: tiny_http::Request, response: Response) -> io::Result<()> {
+    request.respond(
+        tiny_http::Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap()),
+    )
+}
+
+fn dispatch(calls: &Sender<Call>, request: Request) -> Response {
+    let (reply, rx) = crossbeam_channel::bounded(1);
+    if calls.send(Call { request, reply }).is_err() {
+        return Response::error(503, "The practice tool isn't running");
+    }
+
+    rx.recv_timeout(REPLY_TIMEOUT)
+        .unwrap_or_else(|_| Response::error(503, "The game isn't responding"))
+}
+
+fn serve(mut request: tiny_http::Request, token: &str, calls: &Sender<Call>) -> io::Result<()> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+    if !is_authorized(&request, query, token) {
+        return respond(request, Response::error(401, "Missing or invalid token"));
+    }
+
+    if path == "/ws" {
+        return serve_websocket(request, calls);
+    }
+
+    let response = match read_body(&mut request) {
+        Ok(body) => {
+            let method = request.method().as_str().to_string();
+            dispatch(calls, Request { method, path: path.to_string(), body })
+        },
+        Err(e) => Response::error(400, e),
+    };
+    respond(request, response)
+}
+
+/// A request sent over the WebSocket. `id` is echoed back in the response.
+#[derive(Deserialize)]
+struct WsRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    path: String,
+    #[serde(default)]
+    body: Value,
+}
+
+fn serve_websocket(request: tiny_http::Request, calls: &Sender<Call>) -> io::Result<()> {
+    let is_upgrade =
+        header(&request, "Upgrade").map(|u| u.eq_ignore_ascii_case("websocket")).unwrap_or(false);
+    let Some(key) = header(&request, "Sec-WebSocket-Key").filter(|_| is_upgrade) else {
+        return respond(request, Response::error(400, "Expected a WebSocket upgrade"));
+    };
+
+    let accept = derive_accept_key(key.as_bytes());
+    let response = tiny_http::Response::empty(101)
+        .with_header(Header::from_bytes("Sec-WebSocket-Accept", accept).unwrap());
+    let stream = request.upgrade("websocket", response);
+
+    let config = WebSocketConfig {
+        max_message_size: Some(MAX_BODY),
+        max_frame_size: Some(MAX_BODY),
+        ..Default::default()
+    };
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, Some(config));
+
+    loop {
+        let message = match socket.read() {
+            Ok(Message::Text(message)) => message,
+            Ok(_) => continue,
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(io::Error::other(e)),
+        };
+
+        let (id, response) = match serde_json::from_str::<WsRequest>(&message) {
+            Ok(WsRequest { id, method, path, body }) => {
+                (id, dispatch(calls, Request { method, path, body }))
+            },
+            Err(e) => (Value::Null, Response::error(400, format!("Invalid request: {e}"))),
+        };
+
+        let message = json!({ "id": id, "status": response.status, "body": response.body });
+        socket.send(Message::Text(message.to_string())).map_err(io::Error::other)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    use super::*;
+
+    struct MockFlag(Option<bool>);
+
+    impl Flag for MockFlag {
+        fn set(&mut self, value: bool) {
+            self.0 = Some(value);
+        }
+
+        fn get(&self) -> Option<bool> {
+            self.0
+        }
+    }
+
+    struct MockSpawner(Vec<String>);
+
+    impl Widget for MockSpawner {
+        fn render(&mut self, _: &imgui::Ui) {}
+    }
+
+    impl Control for MockSpawner {
+        fn label(&self) -> String {
+            "Item spawner".to_string()
+        }
+
+        fn actions(&self) -> &'static [&'static str] {
+            &["spawn"]
+        }
+
+        fn run(&mut self, _: &str, args: &Value) -> Result<(), String> {
+            self.0.push(arg(args, "name")?);
+            Ok(())
+        }
+
+        fn state(&mut self) -> Value {
+            json!({ "spawned": self.0 })
+        }
+    }
+
+    fn registry() -> Registry {
+        let mut controls = Vec::new();
+        controlled(FlagCommand::new("No damage", MockFlag(Some(false)), None), &mut controls);
+        controlled(FlagCommand::new("One shot", MockFlag(None), None), &mut controls);
+        controlled(MockSpawner(Vec::new()), &mut controls);
+        Registry { controls }
+    }
+
+    fn post(path: &str, body: Value) -> Request {
+        Request { method: "POST".to_string(), path: path.to_string(), body }
+    }
+
+    fn request(method: &str, path: &str) -> Request {
+        Request { method: method.to_string(), path: path.to_string(), body: Value::Null }
+    }
+
+    /// Starts a server whose calls are answered by `registry()` on another
+    /// thread, standing in for the render thread.
+    fn server(token: &str) -> u16 {
+        let server = Server::bind(0, token.to_string()).unwrap();
+        let mut registry = registry();
+        thread::spawn(move || {
+            for call in server.calls.iter() {
+                let response = registry.handle(&call.request, || json!({ "igt": 1234 }));
+                call.reply.send(response).ok();
+            }
+        });
+        server.port
+    }
+
+    /// Sends a raw HTTP request and returns the status and the JSON body.
+    fn http(port: u16, request: &str) -> (u16, Value) {
+        let request = request.replacen("\r\n", "\r\nConnection: close\r\n", 1);
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = registry();
+
+        let response = registry.handle(&request("GET", "/widgets"), || Value::Null);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body[0]["actions"], json!(["toggle", "on", "off"]));
+        assert_eq!(response.body[0]["state"], json!({ "enabled": false }));
+        assert_eq!(response.body[1]["label"], "One shot");
+        assert_eq!(response.body[2]["actions"], json!(["spawn"]));
+
+        let response = registry.handle(&request("POST", "/widgets/0/toggle"), || Value::Null);
+        assert_eq!(response.body["state"], json!({ "enabled": true }));
+        let response = registry.handle(&request("POST", "/widgets/0/on"), || Value::Null);
+        assert_eq!(response.body["state"], json!({ "enabled": true }));
+        let response = registry.handle(&request("POST", "/widgets/0/off/"), || Value::Null);
+        assert_eq!(response.body["state"], json!({ "enabled": false }));
+
+        assert_eq!(
+            registry.handle(&request("POST", "/widgets/1/toggle"), || Value::Null).status,
+            409
+        );
+        assert_eq!(
+            registry.handle(&request("POST", "/widgets/2/toggle"), || Value::Null).status,
+            404
+        );
+        assert_eq!(
+            registry.handle(&request("POST", "/widgets/3/toggle"), || Value::Null).status,
+            404
+        );
+
+        let response = registry.handle(&post("/widgets/2/spawn", json!({})), || Value::Null);
+        assert_eq!(response, Response::error(409, "Missing argument \"name\""));
+        let response =
+            registry.handle(&post("/widgets/2/spawn", json!({ "name": 3 })), || Value::Null);
+        assert_eq!(response.status, 409);
+        let response =
+            registry.handle(&post("/widgets/2/spawn", json!({ "name": "Rowa" })), || Value::Null);
+        assert_eq!(response.body["state"], json!({ "spawned": ["Rowa"] }));
+        assert_eq!(registry.handle(&request("GET", "/widgets/x"), || Value::Null).status, 404);
+        assert_eq!(registry.handle(&request("DELETE", "/widgets/0"), || Value::Null).status, 405);
+        assert_eq!(registry.handle(&request("GET", "/nothing"), || Value::Null).status, 404);
+        assert_eq!(
+            registry.handle(&request("GET", "/state"), || json!({ "igt": 1 })),
+            Response::ok(json!({ "igt": 1 }))
+        );
+    }
+
+    #[test]
+    fn test_http() {
+        let port = server("secret");
+
+        let (status, _) = http(port, "GET /widgets HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 401);
+        let (status, _) = http(port, "GET /widgets?token=wrong HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 401);
+
+        let (status, body) =
+            http(port, "GET /widgets HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n");
+        assert_eq!(status, 200);
+        assert_eq!(body.as_array().unwrap().len(), 3);
+        assert_eq!(body[1]["label"], "One shot");
+
+        let (status, body) = http(port, "POST /widgets/0/toggle?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        assert_eq!(body["state"]["enabled"], true);
+
+        let (status, body) = http(port, "GET /widgets/0?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        assert_eq!(body["state"]["enabled"], true);
+
+        let (status, body) = http(port, "POST /widgets/0/explode?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 404);
+        assert!(body["error"].as_str().unwrap().contains("explode"));
+
+        let (status, _) =
+            http(port, "POST /widgets/0/on?token=secret HTTP/1.1\r\nContent-Length: 3\r\n\r\n{x}");
+        assert_eq!(status, 400);
+
+        let (status, body) = http(port, "GET /state?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({ "igt": 1234 }));
+
+        let body = format!("{{\"x\": \"{}\"}}", "a".repeat(MAX_BODY));
+        let (status, _) = http(
+            port,
+            &format!(
+                "POST /widgets/0/on?token=secret HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        );
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn test_query_token() {
+        let port = server("a&b=c d+%");
+
+        let (status, _) = http(port, "GET /state?token=a&b=c%20d+%25 HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 401);
+        let (status, _) = http(port, "GET /state?token=a%26b%3Dc%20d%2B%25 HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        let (status, _) = http(port, "GET /state?x=1&token=a%26b%3Dc+d%2B%25 HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        let (status, _) =
+            http(port, "GET /state HTTP/1.1\r\nAuthorization: Bearer a&b=c d+%\r\n\r\n");
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn test_websocket() {
+        let port = server("secret");
+        let (mut socket, response) =
+            tungstenite::connect(format!("ws://127.0.0.1:{port}/ws?token=secret")).unwrap();
+        assert_eq!(response.status(), 101);
+
+        let mut send = |message: Value| {
+            socket.send(Message::Text(message.to_string())).unwrap();
+            match socket.read().unwrap() {
+                Message::Text(text) => serde_json::from_str::<Value>(&text).unwrap(),
+                message => panic!("Unexpected message {message:?}"),
+            }
+        };
+
+        let response = send(json!({ "id": 1, "method": "GET", "path": "/state" }));
+        assert_eq!(response, json!({ "id": 1, "status": 200, "body": { "igt": 1234 } }));
+
+        let response = send(json!({ "id": "a", "method": "POST", "path": "/widgets/0/on" }));
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["body"]["state"]["enabled"], true);
+
+        let response = send(json!({ "method": "POST", "path": "/widgets/2/on" }));
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["status"], 404);
+
+        let response = send(json!("nonsense"));
+        assert_eq!(response["status"], 400);
+
+        socket.close(None).unwrap();
+        while !matches!(socket.read(), Err(tungstenite::Error::ConnectionClosed)) {}
+    }
+
+    #[test]
+    fn test_websocket_needs_token() {
+        let port = server("secret");
+        let (status, _) =
+            http(port, "GET /ws HTTP/1.1\r\nUpgrade: websocket\r\nSec-WebSocket-Key: abc\r\n\r\n");
+        assert_eq!(status, 401);
+    }
+
+    #[test]
+    fn test_not_running() {
+        let server = Server::bind(0, "secret".to_string()).unwrap();
+        let port = server.port;
+        drop(server);
+
+        let (status, _) = http(port, "GET /state?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 503);
+    }
+}
@@ -0,0 +1,261 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use hudhook::tracing::error;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use serde::Deserialize;
+
+use crate::util::{get_dll_relative_path, write_text, Igt};
+use crate::widgets::stats;
+use crate::widgets::warp::area_name;
+
+/// A text file rewritten from a template, for OBS text sources and the like.
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct TextOutput {
+    pub(crate) template: String,
+    /// Relative paths are relative to the DLL's directory.
+    pub(crate) path: PathBuf,
+    /// Maximum number of writes per second.
+    #[serde(default = "default_rate")]
+    pub(crate) rate: f32,
+}
+
+fn default_rate() -> f32 {
+    4.
+}
+
+/// Replaces `{name}` placeholders in `template` with their values. `{{` and
+/// `}}` are literal braces, and placeholders without a value are kept as
+/// they are.
+pub(crate) fn render_template(
+    template: &str,
+    value: impl Fn(&str) -> Option<String>,
+    out: &mut String,
+) {
+    out.clear();
+    let mut rest = template;
+
+    while let Some(idx) = rest.find(['{', '}']) {
+        out.push_str(&rest[..idx]);
+        let tail = &rest[idx..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+        } else if let Some(end) = tail.starts_with('{').then(|| tail.find('}')).flatten() {
+            match value(&tail[1..end]) {
+                Some(value) => out.push_str(&value),
+                None => out.push_str(&tail[..=end]),
+            }
+            rest = &tail[end + 1..];
+        } else {
+            out.push_str(&tail[..1]);
+            rest = &tail[1..];
+        }
+    }
+
+    out.push_str(rest);
+}
+
+/// Game state the placeholders are filled from.
+#[derive(Debug, Default)]
+struct State {
+    igt: Option<usize>,
+    map_id: Option<MapId>,
+    area: Option<String>,
+    position: Option<[f32; 5]>,
+    hp: Option<[u32; 3]>,
+    fp: Option<[u32; 3]>,
+    sp: Option<[u32; 3]>,
+    flags: Vec<&'static str>,
+    deaths: u32,
+    attempts: u32,
+}
+
+impl State {
+    fn read(pointers: &Pointers, flags: &[(&'static str, Bitflag<u8>)]) -> Self {
+        let map_id = pointers.global_position.read_map_id().map(MapId);
+
+        State {
+            igt: pointers.igt.read(),
+            map_id,
+            area: map_id.and_then(|map_id| area_name(map_id, &pointers.msg_repository)),
+            position: pointers.global_position.read(),
+            hp: pointers.player_hp.read(),
+            fp: pointers.player_fp.read(),
+            sp: pointers.player_sp.read(),
+            flags: flags
+                .iter()
+                .filter(|(_, flag)| flag.get().unwrap_or(false))
+                .map(|(name, _)| *name)
+                .collect(),
+            deaths: stats::deaths(),
+            attempts: stats::attempts(),
+        }
+    }
+
+    /// The value of a placeholder, empty if it can't be read right now, or
+    /// `None` if there's no such placeholder.
+    fn value(&self, name: &str) -> Option<String> {
+        fn or_empty<T>(value: Option<T>, f: impl Fn(T) -> String) -> String {
+            value.map(f).unwrap_or_default()
+        }
+
+        let value = match name {
+            "igt" => or_empty(self.igt, |igt| Igt(igt).to_string()),
+            "map" => or_empty(self.map_id, |map_id| map_id.to_string()),
+            "area" => self.area.clone().unwrap_or_default(),
+            "x" => or_empty(self.position, |[x, ..]| format!("{x:.2}")),
+            "y" => or_empty(self.position, |[_, y, ..]| format!("{y:.2}")),
+            "z" => or_empty(self.position, |[_, _, z, ..]| format!("{z:.2}")),
+            "hp" => or_empty(self.hp, |[hp, ..]| hp.to_string()),
+            "max_hp" => or_empty(self.hp, |[.., max]| max.to_string()),
+            "fp" => or_empty(self.fp, |[fp, ..]| fp.to_string()),
+            "max_fp" => or_empty(self.fp, |[.., max]| max.to_string()),
+            "sp" => or_empty(self.sp, |[sp, ..]| sp.to_string()),
+            "max_sp" => or_empty(self.sp, |[.., max]| max.to_string()),
+            "flags" => self.flags.join(", "),
+            "deaths" => self.deaths.to_string(),
+            "attempts" => self.attempts.to_string(),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+}
+
+struct Output {
+    template: String,
+    path: PathBuf,
+    interval: Duration,
+    written_at: Option<Instant>,
+    content: String,
+    buf: String,
+}
+
+/// Keeps the configured text outputs up to date.
+pub(crate) struct TextOutputs {
+    outputs: Vec<Output>,
+    flags: Vec<(&'static str, Bitflag<u8>)>,
+    logs: Vec<String>,
+}
+
+impl TextOutputs {
+    pub(crate) fn new(outputs: Vec<TextOutput>, flags: Vec<(&'static str, Bitflag<u8>)>) -> Self {
+        let outputs = outputs
+            .into_iter()
+            .map(|output| Output {
+                template: output.template,
+                path: get_dll_relative_path(&output.path).unwrap_or(output.path),
+                interval: Duration::from_secs_f32(1. / output.rate.max(0.1)),
+                written_at: None,
+                content: String::new(),
+                buf: String::new(),
+            })
+            .collect();
+
+        TextOutputs { outputs, flags, logs: Vec::new() }
+    }
+
+    pub(crate) fn update(&mut self, pointers: &Pointers, tx: &Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+
+        let now = Instant::now();
+        let is_due = |output: &Output| {
+            output.written_at.map(|t| now.duration_since(t) >= output.interval).unwrap_or(true)
+        };
+
+        if !self.outputs.iter().any(is_due) {
+            return;
+        }
+
+        let state = State::read(pointers, &self.flags);
+        let logs = &mut self.logs;
+        self.outputs.retain_mut(|output| {
+            if !is_due(output) {
+                return true;
+            }
+
+            output.written_at = Some(now);
+            render_template(&output.template, |name| state.value(name), &mut output.buf);
+            if output.buf == output.content {
+                return true;
+            }
+
+            std::mem::swap(&mut output.buf, &mut output.content);
+            match write_text(&output.path, &output.content) {
+                Ok(()) => true,
+                Err(e) => {
+                    error!("{e}");
+                    logs.push(e);
+                    false
+                },
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str) -> String {
+        let mut out = String::new();
+        render_template(
+            template,
+            |name| match name {
+                "igt" => Some("01:02:03.45".to_string()),
+                "empty" => Some(String::new()),
+                _ => None,
+            },
+            &mut out,
+        );
+        out
+    }
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(render("IGT {igt}"), "IGT 01:02:03.45");
+        assert_eq!(render("{igt}{igt}"), "01:02:03.4501:02:03.45");
+        assert_eq!(render("[{empty}]"), "[]");
+        assert_eq!(render("{unknown} {igt}"), "{unknown} 01:02:03.45");
+        assert_eq!(render("{{igt}} {{{igt}}}"), "{igt} {01:02:03.45}");
+        assert_eq!(render("unclosed {igt"), "unclosed {igt");
+        assert_eq!(render("stray } brace"), "stray } brace");
+        assert_eq!(render("no placeholders"), "no placeholders");
+        assert_eq!(render("ünïcode {igt} ✓"), "ünïcode 01:02:03.45 ✓");
+    }
+
+    #[test]
+    fn test_state_values() {
+        let state = State {
+            igt: Some(3_723_456),
+            map_id: Some(MapId::new(10, 0, 0, 0)),
+            area: Some("Stormveil Castle".to_string()),
+            position: Some([1.234, -5.0, 100.0, 0.0, 0.0]),
+            hp: Some([420, 500, 600]),
+            flags: vec!["no_damage", "one_shot"],
+            deaths: 12,
+            ..Default::default()
+        };
+
+        assert_eq!(state.value("igt").unwrap(), "01:02:03.45");
+        assert_eq!(state.value("map").unwrap(), "m10_00_00_00");
+        assert_eq!(state.value("area").unwrap(), "Stormveil Castle");
+        assert_eq!(state.value("x").unwrap(), "1.23");
+        assert_eq!(state.value("y").unwrap(), "-5.00");
+        assert_eq!(state.value("hp").unwrap(), "420");
+        assert_eq!(state.value("max_hp").unwrap(), "600");
+        assert_eq!(state.value("fp").unwrap(), "");
+        assert_eq!(state.value("flags").unwrap(), "no_damage, one_shot");
+        assert_eq!(state.value("deaths").unwrap(), "12");
+        assert_eq!(state.value("nothing"), None);
+
+        let mut out = String::new();
+        render_template("{area} ({x}, {z}) HP {hp}/{max_hp}", |n| state.value(n), &mut out);
+        assert_eq!(out, "Stormveil Castle (1.23, 100.00) HP 420/600");
+    }
+}
@@ -3,11 +3,14 @@ use std::fs::OpenOptions;
 use std::io::Write;
 use std::os::windows::ffi::OsStrExt;
 use std::os::windows::prelude::OsStringExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
 use hudhook::inject::Process;
 use hudhook::tracing::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::prelude::*;
 use windows::core::{PCSTR, *};
@@ -19,6 +22,31 @@ use windows::Win32::System::LibraryLoader::{
 use windows::Win32::System::Threading::{QueryFullProcessImageNameW, PROCESS_NAME_FORMAT};
 use windows::Win32::UI::WindowsAndMessaging::*;
 
+/// Milliseconds of IGT, displayed as `hh:mm:ss.cc`. Timers and attempt
+/// durations are displayed through this too.
+pub struct Igt(pub usize);
+
+impl From<Duration> for Igt {
+    fn from(duration: Duration) -> Self {
+        Igt(duration.as_millis() as usize)
+    }
+}
+
+impl std::fmt::Display for Igt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Igt(igt) = *self;
+        let seconds = igt / 1000;
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            igt % 1000 / 10
+        )
+    }
+}
+
 /// Create a Windows message box.
 pub fn message_box<S: AsRef<str>, T: AsRef<str>>(
     caption: S,
@@ -62,6 +90,43 @@ pub fn get_dll_path() -> Option<PathBuf> {
     Some(OsString::from_wide(&sz_filename[..len]).into())
 }
 
+/// Return the path of a file in the DLL's directory.
+pub fn get_dll_relative_path(path: impl AsRef<Path>) -> Option<PathBuf> {
+    get_dll_path().map(|mut dll_path| {
+        dll_path.pop();
+        dll_path.push(path);
+        dll_path
+    })
+}
+
+/// Read a TOML file. A file that doesn't exist yet reads as the default value.
+pub fn load_toml<T: DeserializeOwned + Default>(path: &Path) -> std::result::Result<T, String> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+    toml::from_str(&content).map_err(|e| format!("Couldn't parse {}: {e}", path.display()))
+}
+
+/// Write a TOML file, creating its directory if needed.
+pub fn write_toml<T: Serialize>(path: &Path, value: &T) -> std::result::Result<(), String> {
+    let content = toml::to_string(value)
+        .map_err(|e| format!("Couldn't serialize {}: {e}", path.display()))?;
+
+    write_text(path, &content)
+}
+
+/// Write a text file, creating its directory if needed.
+pub fn write_text(path: &Path, content: &str) -> std::result::Result<(), String> {
+    path.parent()
+        .map(std::fs::create_dir_all)
+        .transpose()
+        .and_then(|_| std::fs::write(path, content))
+        .map_err(|e| format!("Couldn't write {}: {e}", path.display()))
+}
+
 /// Retrieve the DLL path from the current executable's directory.
 pub fn get_dll_path_exe() -> Result<PathBuf> {
     let mut dll_path = std::env::current_exe().unwrap();
@@ -138,3 +203,15 @@ pub fn tracing_init() {
 
     tracing_subscriber::registry().with(LevelFilter::DEBUG).with(stdout_layer).init();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_igt() {
+        assert_eq!(Igt(0).to_string(), "00:00:00.00");
+        assert_eq!(Igt::from(Duration::from_millis(75_129)).to_string(), "00:01:15.12");
+        assert_eq!(Igt::from(Duration::from_millis(3_723_004)).to_string(), "01:02:03.00");
+    }
+}
@@ -1,8 +1,10 @@
 use libeldenring::memedit::PointerChain;
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::flag::{Flag, FlagWidget};
+use practice_tool_core::widgets::flag::Flag;
 use practice_tool_core::widgets::Widget;
 
+use crate::remote::{Control, FlagCommand};
+
 struct ActionFreeze {
     ptr: PointerChain<u8>,
     state: bool,
@@ -37,6 +39,6 @@ pub(crate) fn action_freeze(
     ptr: PointerChain<u8>,
     states: (u8, u8),
     key: Option<Key>,
-) -> Box<dyn Widget> {
-    Box::new(FlagWidget::new("Action freeze", ActionFreeze::new(ptr, states), key))
+) -> impl Widget + Control {
+    FlagCommand::new("Action freeze", ActionFreeze::new(ptr, states), key)
 }
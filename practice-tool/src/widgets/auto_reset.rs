@@ -0,0 +1,361 @@
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use libeldenring::prelude::{Bitflag, Position as ErPosition};
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::position::PositionStorage;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::{json, Value};
+
+use super::boss_reset::{boss_flag, Refill};
+use super::position::SavePosition;
+use super::stats::{record, StatEvent};
+use super::target::{entity_hp, target_entity};
+use crate::remote::Control;
+use crate::util::Igt;
+
+/// Time after a reset during which deaths aren't checked, so the writes have
+/// time to land before HP is read again.
+const RESET_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// The boss counts as killed at or below its max HP divided by this. A boss
+/// at 0 HP is already dead and writing its HP back doesn't revive it, so the
+/// reset has to happen before the last hit lands.
+const BOSS_KILL_HP_DIVISOR: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    PlayerDeath,
+    BossDeath,
+}
+
+impl Outcome {
+    fn label(&self) -> &'static str {
+        match self {
+            Outcome::PlayerDeath => "died",
+            Outcome::BossDeath => "killed",
+        }
+    }
+}
+
+/// Counts attempts and times them.
+#[derive(Debug)]
+struct Attempts {
+    count: u32,
+    started: Instant,
+    cooldown_until: Instant,
+    last: Option<(Outcome, Duration)>,
+}
+
+impl Attempts {
+    fn new(now: Instant) -> Self {
+        Attempts { count: 1, started: now, cooldown_until: now, last: None }
+    }
+
+    /// Checks whether the attempt is over. The player counts as dead at 1 HP,
+    /// as that's where `no_dead` keeps them, and the boss as killed once it
+    /// drops to a tenth of its max HP. Boss HP is `[hp, base_max, max]`.
+    fn check(
+        &self,
+        player_hp: Option<u32>,
+        boss_hp: Option<[u32; 3]>,
+        now: Instant,
+    ) -> Option<Outcome> {
+        if now < self.cooldown_until {
+            None
+        } else if player_hp.map(|hp| hp <= 1).unwrap_or(false) {
+            Some(Outcome::PlayerDeath)
+        } else if boss_hp.map(|[hp, _, max]| hp <= max / BOSS_KILL_HP_DIVISOR).unwrap_or(false) {
+            Some(Outcome::BossDeath)
+        } else {
+            None
+        }
+    }
+
+    fn finish(&mut self, outcome: Outcome, now: Instant) -> Duration {
+        let duration = now - self.started;
+        self.last = Some((outcome, duration));
+        self.count += 1;
+        self.started = now;
+        self.cooldown_until = now + RESET_COOLDOWN;
+        duration
+    }
+}
+
+/// Puts the player back at the arena entrance, refills them and heals the
+/// boss whenever either of them dies, without reloading the area. Attempts
+/// are counted in the stats under the configured boss name, if any.
+pub(crate) struct AutoReset {
+    label: String,
+    hotkey: Option<Key>,
+
+    storage: SavePosition,
+    refill: Refill,
+    no_dead: Bitflag<u8>,
+    reapply: Vec<Bitflag<u8>>,
+
+    boss: Option<usize>,
+    boss_name: Option<String>,
+    attempts: Option<Attempts>,
+    no_dead_before: Option<bool>,
+    indicator: String,
+
+    logs: Vec<String>,
+}
+
+impl AutoReset {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        global_position: ErPosition,
+        chunk_position: ErPosition,
+        torrent_chunk_position: ErPosition,
+        refill: Refill,
+        no_dead: Bitflag<u8>,
+        reapply: Vec<Bitflag<u8>>,
+        boss_name: Option<String>,
+        hotkey: Option<Key>,
+    ) -> Self {
+        let label = match &hotkey {
+            Some(k) => format!("Auto-reset ({k})"),
+            None => "Auto-reset".to_string(),
+        };
+
+        let mut logs = Vec::new();
+        let boss_name = boss_name.filter(|name| {
+            let known = boss_flag(name).is_some();
+            if !known {
+                logs.push(format!("Unknown boss \"{name}\", attempts won't be recorded"));
+            }
+            known
+        });
+
+        AutoReset {
+            label,
+            hotkey,
+            storage: SavePosition::new(
+                global_position,
+                chunk_position,
+                torrent_chunk_position,
+                0.0,
+                None,
+            ),
+            refill,
+            no_dead,
+            reapply,
+            boss: None,
+            boss_name,
+            attempts: None,
+            no_dead_before: None,
+            indicator: String::new(),
+            logs,
+        }
+    }
+
+    fn toggle(&mut self) {
+        if self.attempts.is_some() {
+            self.disarm();
+        } else {
+            self.arm();
+        }
+    }
+
+    fn arm(&mut self) {
+        if self.storage.saved().is_none() {
+            self.logs.push("Save the arena position before starting auto-reset".to_string());
+            return;
+        }
+
+        self.no_dead_before = self.no_dead.get();
+        self.no_dead.set(true);
+        self.reapply.iter().for_each(|flag| flag.set(true));
+        self.attempts = Some(Attempts::new(Instant::now()));
+        self.logs.push("Auto-reset started".to_string());
+    }
+
+    fn disarm(&mut self) {
+        if let Some(no_dead) = self.no_dead_before.take() {
+            self.no_dead.set(no_dead);
+        }
+        self.attempts = None;
+        self.logs.push("Auto-reset stopped".to_string());
+    }
+
+    fn save_position(&mut self) {
+        self.storage.save();
+        if self.storage.saved().is_none() {
+            self.logs.push("Couldn't read the player's position".to_string());
+        }
+    }
+
+    fn lock_boss(&mut self) {
+        self.boss = target_entity();
+        match self.boss {
+            Some(_) => self.logs.push("Boss locked".to_string()),
+            None => self.logs.push("Lock on to the boss with target info enabled".to_string()),
+        }
+    }
+
+    fn reset(&mut self, outcome: Outcome, now: Instant) {
+        let Some(attempts) = self.attempts.as_mut() else {
+            return;
+        };
+
+        let count = attempts.count;
+        let duration = attempts.finish(outcome, now);
+
+        self.storage.load();
+        self.refill.apply();
+        if let Some(boss_hp) = self.boss.map(entity_hp) {
+            match boss_hp.read() {
+                Some([0, ..]) => {
+                    self.boss = None;
+                    self.logs.push("The boss died before the reset, reload to revive it".into());
+                },
+                Some([_, base_max, max]) => {
+                    boss_hp.write([max, base_max, max]);
+                },
+                None => {},
+            }
+        }
+        self.reapply.iter().for_each(|flag| flag.set(true));
+
+        if let Some(name) = &self.boss_name {
+            record(StatEvent::BossAttempt(name.clone()));
+        }
+
+        self.logs.push(format!(
+            "Attempt {count}: {} after {}",
+            outcome.label(),
+            Igt::from(duration)
+        ));
+    }
+}
+
+impl Widget for AutoReset {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let mut armed = self.attempts.is_some();
+        if ui.checkbox(&self.label, &mut armed) {
+            self.toggle();
+        }
+
+        if ui.button_with_size("Save arena position", [button_width, button_height]) {
+            self.save_position();
+        }
+
+        if ui.button_with_size("Lock boss", [button_width, button_height]) {
+            self.lock_boss();
+        }
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        let Some(attempts) = self.attempts.as_ref() else {
+            return;
+        };
+
+        self.indicator.clear();
+        write!(
+            self.indicator,
+            "Attempt {} {}",
+            attempts.count,
+            Igt::from(attempts.started.elapsed())
+        )
+        .ok();
+        if let Some((outcome, duration)) = attempts.last {
+            write!(self.indicator, " (last: {} {})", outcome.label(), Igt::from(duration)).ok();
+        }
+
+        ui.text(&self.indicator);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if self.hotkey.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.toggle();
+        }
+
+        let Some(attempts) = self.attempts.as_ref() else {
+            return;
+        };
+
+        let now = Instant::now();
+        let player_hp = self.refill.hp.read().map(|[hp, ..]| hp);
+        let boss_hp = self.boss.and_then(|boss| entity_hp(boss).read());
+
+        if let Some(outcome) = attempts.check(player_hp, boss_hp, now) {
+            self.reset(outcome, now);
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+impl Control for AutoReset {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["toggle", "on", "off", "save_position", "lock_boss"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        match (action, self.attempts.is_some()) {
+            ("save_position", _) => self.save_position(),
+            ("lock_boss", _) => self.lock_boss(),
+            ("on", true) | ("off", false) => {},
+            ("toggle" | "on", false) if self.storage.saved().is_none() => {
+                return Err("Save the arena position before starting auto-reset".to_string());
+            },
+            _ => self.toggle(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({
+            "enabled": self.attempts.is_some(),
+            "attempt": self.attempts.as_ref().map(|a| a.count),
+            "position_saved": self.storage.saved().is_some(),
+            "boss_locked": self.boss.is_some(),
+            "boss": self.boss_name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attempts() {
+        let start = Instant::now();
+        let mut attempts = Attempts::new(start);
+
+        let boss = |hp| Some([hp, 1000, 1000]);
+        assert_eq!(attempts.check(Some(500), boss(1000), start), None);
+        assert_eq!(attempts.check(Some(500), boss(101), start), None);
+        assert_eq!(attempts.check(None, None, start), None);
+        assert_eq!(attempts.check(Some(1), boss(1000), start), Some(Outcome::PlayerDeath));
+        assert_eq!(attempts.check(Some(500), boss(100), start), Some(Outcome::BossDeath));
+        assert_eq!(attempts.check(Some(500), boss(0), start), Some(Outcome::BossDeath));
+
+        let died = start + Duration::from_secs(75);
+        assert_eq!(attempts.finish(Outcome::PlayerDeath, died), Duration::from_secs(75));
+        assert_eq!(attempts.count, 2);
+        assert_eq!(attempts.last, Some((Outcome::PlayerDeath, Duration::from_secs(75))));
+
+        assert_eq!(attempts.check(Some(1), None, died + Duration::from_millis(500)), None);
+        assert_eq!(
+            attempts.check(Some(1), None, died + RESET_COOLDOWN),
+            Some(Outcome::PlayerDeath)
+        );
+    }
+}
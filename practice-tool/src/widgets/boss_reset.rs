@@ -0,0 +1,426 @@
+use std::path::PathBuf;
+
+use imgui::InputText;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::stats::{record, StatEvent};
+use super::warp::{nearest_grace, LocationWarp};
+use super::{begin_popup, close_button, load_file, popup_anchor, string_match, write_file};
+use crate::remote::{select, Control};
+use crate::util::{get_dll_relative_path, load_toml};
+
+const POPUP_TAG: &str = "##boss-reset";
+
+/// Goods row ids of the Crimson and Cerulean flasks, +0 to +12. Even ids are
+/// full flasks, odd ids their empty counterparts.
+const CRIMSON_FLASK_ROWS: std::ops::RangeInclusive<u32> = 1000..=1025;
+const CERULEAN_FLASK_ROWS: std::ops::RangeInclusive<u32> = 1050..=1075;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Boss {
+    name: String,
+    flag: u32,
+    map_id: MapId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    grace: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotation: Option<[f32; 2]>,
+}
+
+impl Boss {
+    fn position(&self) -> Option<[f32; 5]> {
+        let [x, y, z] = self.position?;
+        let [r1, r2] = self.rotation.unwrap_or_default();
+        Some([x, y, z, r1, r2])
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BossFile {
+    #[serde(default)]
+    bosses: Vec<Boss>,
+}
+
+/// Adds the user's bosses to the built-in ones. User entries replace built-in
+/// entries with the same name.
+fn merge_bosses(mut bosses: Vec<Boss>, user: &[Boss]) -> Vec<Boss> {
+    for boss in user {
+        match bosses.iter_mut().find(|b| b.name == boss.name) {
+            Some(b) => *b = boss.clone(),
+            None => bosses.push(boss.clone()),
+        }
+    }
+    bosses
+}
+
+fn builtin_bosses() -> Vec<Boss> {
+    toml::from_str::<BossFile>(include_str!("bosses.toml")).unwrap().bosses
+}
+
+fn bosses_path() -> Option<PathBuf> {
+    get_dll_relative_path("bosses.toml")
+}
+
+/// Looks up a boss's defeated flag by name, among the built-in bosses and the
+/// ones in the user's bosses file.
+pub(crate) fn boss_flag(name: &str) -> Option<u32> {
+    let user_bosses = bosses_path()
+        .and_then(|path| load_toml::<BossFile>(&path).ok())
+        .map(|file| file.bosses)
+        .unwrap_or_default();
+    merge_bosses(builtin_bosses(), &user_bosses)
+        .into_iter()
+        .find(|boss| boss.name == name)
+        .map(|boss| boss.flag)
+}
+
+/// Pointers used to restore HP, FP and flask charges after a reset.
+pub(crate) struct Refill {
+    pub(crate) hp: PointerChain<[u32; 3]>,
+    pub(crate) fp: PointerChain<[u32; 3]>,
+    pub(crate) crimson_flasks: PointerChain<u8>,
+    pub(crate) cerulean_flasks: PointerChain<u8>,
+    pub(crate) inventory: Inventory,
+}
+
+impl Refill {
+    pub(crate) fn apply(&self) {
+        for vital in [&self.hp, &self.fp] {
+            if let Some([_, base_max, max]) = vital.read() {
+                vital.write([max, base_max, max]);
+            }
+        }
+
+        let crimson = self.crimson_flasks.read().unwrap_or(0) as u32;
+        let cerulean = self.cerulean_flasks.read().unwrap_or(0) as u32;
+
+        for (slot, item) in self.inventory.items().unwrap_or_default() {
+            let Some((ItemCategory::Goods, row_id)) = ItemCategory::from_item_id(item.item_id)
+            else {
+                continue;
+            };
+
+            let charges = if CRIMSON_FLASK_ROWS.contains(&row_id) {
+                crimson
+            } else if CERULEAN_FLASK_ROWS.contains(&row_id) {
+                cerulean
+            } else {
+                continue;
+            };
+
+            if charges > 0 {
+                self.inventory.set_item(slot, ItemCategory::Goods.item_id(row_id & !1), charges);
+            }
+        }
+    }
+}
+
+/// Clears a boss's defeated flag and reloads its map by warping to a nearby
+/// grace, optionally restoring HP, FP and flasks once the map has loaded.
+pub(crate) struct BossReset {
+    label: String,
+    label_close: String,
+    hotkey_reset: Option<Key>,
+    hotkey_close: Key,
+
+    event_flags: EventFlags,
+    global_position: Position,
+    warp: LocationWarp,
+    refill: Refill,
+
+    bosses: Vec<Boss>,
+    visible: Vec<bool>,
+    selected: Option<usize>,
+    filter_string: String,
+    restore: bool,
+    refill_pending: bool,
+
+    path: Option<PathBuf>,
+    user_bosses: Vec<Boss>,
+
+    logs: Vec<String>,
+}
+
+impl BossReset {
+    pub(crate) fn new(
+        event_flags: EventFlags,
+        global_position: Position,
+        warp: LocationWarp,
+        refill: Refill,
+        restore: bool,
+        hotkey_reset: Option<Key>,
+        hotkey_close: Key,
+    ) -> Self {
+        let label = match &hotkey_reset {
+            Some(k) => format!("Boss reset ({k})"),
+            None => "Boss reset".to_string(),
+        };
+
+        let path = bosses_path();
+
+        let mut logs = Vec::new();
+        let user_bosses = load_file::<BossFile>(path.as_deref(), &mut logs).bosses;
+
+        let bosses = merge_bosses(builtin_bosses(), &user_bosses);
+        let visible = vec![true; bosses.len()];
+
+        BossReset {
+            label,
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_reset,
+            hotkey_close,
+            event_flags,
+            global_position,
+            warp,
+            refill,
+            bosses,
+            visible,
+            selected: None,
+            filter_string: String::new(),
+            restore,
+            refill_pending: false,
+            path,
+            user_bosses,
+            logs,
+        }
+    }
+
+    fn update_filter(&mut self) {
+        for (boss, visible) in self.bosses.iter().zip(self.visible.iter_mut()) {
+            *visible =
+                self.filter_string.is_empty() || string_match(&self.filter_string, &boss.name);
+        }
+    }
+
+    fn reset(&mut self) {
+        let Some(boss) = self.selected.and_then(|idx| self.bosses.get(idx)).cloned() else {
+            self.logs.push("No boss selected".to_string());
+            return;
+        };
+
+        let Some(entity_id) =
+            boss.grace.or_else(|| nearest_grace(boss.map_id, boss.position().unwrap_or_default()))
+        else {
+            self.logs.push(format!("No grace found on {}", boss.map_id));
+            return;
+        };
+
+        if self.event_flags.set_flag(boss.flag, false).is_none() {
+            self.logs.push(format!("Couldn't clear event flag {}", boss.flag));
+            return;
+        }
+
+        if self.warp.reload_at(entity_id, boss.map_id, boss.position()) {
+            self.logs.push(format!("Resetting {}", boss.name));
+            self.refill_pending = self.restore;
+            record(StatEvent::BossAttempt(boss.name));
+        }
+    }
+
+    /// Stores the player's current position as the selected boss's arena
+    /// entry in the user's bosses file.
+    fn save_position(&mut self) {
+        let Some(idx) = self.selected else {
+            self.logs.push("No boss selected".to_string());
+            return;
+        };
+
+        let (Some([x, y, z, r1, r2]), Some(map_id)) =
+            (self.global_position.read(), self.global_position.read_map_id())
+        else {
+            self.logs.push("Couldn't read the player's position".to_string());
+            return;
+        };
+
+        let boss = &mut self.bosses[idx];
+        boss.map_id = MapId(map_id);
+        boss.position = Some([x, y, z]);
+        boss.rotation = Some([r1, r2]);
+        let boss = boss.clone();
+
+        self.user_bosses =
+            merge_bosses(std::mem::take(&mut self.user_bosses), std::slice::from_ref(&boss));
+        self.logs.push(format!("Saved arena position for {}", boss.name));
+        self.write_file();
+    }
+
+    fn write_file(&mut self) {
+        let file = BossFile { bosses: self.user_bosses.clone() };
+        write_file(self.path.as_deref(), &file, &mut self.logs);
+    }
+}
+
+impl Widget for BossReset {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            {
+                let _tok = ui.push_item_width(-1.);
+                if InputText::new(ui, "##boss-reset-filter", &mut self.filter_string)
+                    .hint("Filter...")
+                    .build()
+                {
+                    self.update_filter();
+                }
+            }
+
+            ui.child_window("##boss-reset-list").size([400., 200.]).build(|| {
+                for (idx, boss) in self.bosses.iter().enumerate() {
+                    if !self.visible[idx] {
+                        continue;
+                    }
+
+                    let defeated = match self.event_flags.get_flag(boss.flag) {
+                        Some(true) => " (defeated)",
+                        _ => "",
+                    };
+                    let label = format!("{}{defeated}##{idx}", boss.name);
+                    if ui.selectable_config(&label).selected(self.selected == Some(idx)).build() {
+                        self.selected = Some(idx);
+                    }
+                }
+            });
+
+            ui.checkbox("Restore HP, FP and flasks", &mut self.restore);
+
+            if ui.button_with_size("Reset boss", [196., button_height]) {
+                self.reset();
+            }
+            ui.same_line();
+            if ui.button_with_size("Save arena position", [196., button_height]) {
+                self.save_position();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.warp.update();
+
+        if self.refill_pending && !self.warp.is_pending() {
+            self.refill_pending = false;
+            self.refill.apply();
+            self.logs.push("Restored HP, FP and flasks".to_string());
+        }
+
+        if self.hotkey_reset.map(|k| k.is_pressed(ui)).unwrap_or(false) && !ui.is_any_item_active()
+        {
+            self.reset();
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.warp.log(&tx);
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// Both actions act on the selected boss, or on one picked by name:
+/// `{ "name": "Margit, the Fell Omen" }`.
+impl Control for BossReset {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["reset", "save_position"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if args.get("name").is_some() {
+            self.selected = Some(select(&self.bosses, |b| &b.name, args)?);
+        } else if self.selected.is_none() {
+            return Err("No boss selected".to_string());
+        }
+
+        match action {
+            "reset" => self.reset(),
+            _ => self.save_position(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({
+            "bosses": self.bosses.iter().map(|b| &b.name).collect::<Vec<_>>(),
+            "selected": self.selected.map(|idx| &self.bosses[idx].name),
+            "pending": self.warp.is_pending(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Vec<Boss> {
+        toml::from_str::<BossFile>(content).unwrap().bosses
+    }
+
+    #[test]
+    fn test_builtin_bosses() {
+        let bosses = builtin_bosses();
+        assert!(!bosses.is_empty());
+
+        for (idx, boss) in bosses.iter().enumerate() {
+            assert!(bosses[..idx].iter().all(|b| b.name != boss.name && b.flag != boss.flag));
+            assert!(bosses[..idx].iter().all(|b| b.grace != boss.grace), "{}", boss.name);
+            assert!(boss.grace.is_some(), "{} has no grace", boss.name);
+        }
+    }
+
+    #[test]
+    fn test_merge_bosses() {
+        let builtin = parse(
+            r#"
+            [[bosses]]
+            name = "Godrick the Grafted"
+            flag = 10000800
+            map_id = "m10_00_00_00"
+            "#,
+        );
+
+        let user = parse(
+            r#"
+            [[bosses]]
+            name = "Godrick the Grafted"
+            flag = 10000800
+            map_id = "m10_00_00_00"
+            grace = 10001950
+            position = [1.0, 2.0, 3.0]
+
+            [[bosses]]
+            name = "Custom boss"
+            flag = 1234
+            map_id = "m60_42_36_00"
+            "#,
+        );
+
+        let bosses = merge_bosses(builtin, &user);
+        assert_eq!(bosses, user);
+        assert_eq!(bosses[0].position(), Some([1.0, 2.0, 3.0, 0.0, 0.0]));
+        assert_eq!(bosses[1].position(), None);
+
+        let content = toml::to_string(&BossFile { bosses: bosses.clone() }).unwrap();
+        assert_eq!(parse(&content), bosses);
+    }
+}
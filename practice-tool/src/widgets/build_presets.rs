@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use imgui::InputText;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{begin_popup, close_button, load_file, popup_anchor, write_file};
+use crate::remote::{arg, select, Control};
+use crate::util::get_dll_relative_path;
+
+const POPUP_TAG: &str = "##build-presets";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BuildPreset {
+    name: String,
+    vigor: i32,
+    mind: i32,
+    endurance: i32,
+    strength: i32,
+    dexterity: i32,
+    intelligence: i32,
+    faith: i32,
+    arcane: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    level: Option<i32>,
+}
+
+impl BuildPreset {
+    fn capture(name: String, stats: &CharacterStats) -> Self {
+        BuildPreset {
+            name,
+            vigor: stats.vigor,
+            mind: stats.mind,
+            endurance: stats.endurance,
+            strength: stats.strength,
+            dexterity: stats.dexterity,
+            intelligence: stats.intelligence,
+            faith: stats.faith,
+            arcane: stats.arcane,
+            level: Some(stats.level),
+        }
+    }
+
+    fn apply(&self, stats: &mut CharacterStats, auto_level: bool) {
+        stats.vigor = self.vigor;
+        stats.mind = self.mind;
+        stats.endurance = self.endurance;
+        stats.strength = self.strength;
+        stats.dexterity = self.dexterity;
+        stats.intelligence = self.intelligence;
+        stats.faith = self.faith;
+        stats.arcane = self.arcane;
+
+        if auto_level {
+            stats.level = level_from_attributes(&stats.attributes()).max(1);
+        } else if let Some(level) = self.level {
+            stats.level = level;
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildsFile {
+    #[serde(default)]
+    builds: Vec<BuildPreset>,
+}
+
+/// Named attribute presets, persisted to `builds.toml` next to the DLL.
+pub(crate) struct BuildPresets {
+    label: String,
+    label_close: String,
+    hotkey_close: Key,
+
+    ptr: PointerChain<CharacterStats>,
+
+    path: Option<PathBuf>,
+    builds: Vec<BuildPreset>,
+    selected: Option<usize>,
+    auto_level: bool,
+    new_name: String,
+
+    logs: Vec<String>,
+}
+
+impl BuildPresets {
+    pub(crate) fn new(ptr: PointerChain<CharacterStats>, hotkey_close: Key) -> Self {
+        let label = "Build presets".to_string();
+        let label_close = format!("Close ({hotkey_close})");
+
+        let path = get_dll_relative_path("builds.toml");
+
+        let mut logs = Vec::new();
+        let builds = load_file::<BuildsFile>(path.as_deref(), &mut logs).builds;
+
+        BuildPresets {
+            label,
+            label_close,
+            hotkey_close,
+            ptr,
+            path,
+            builds,
+            selected: None,
+            auto_level: true,
+            new_name: String::new(),
+            logs,
+        }
+    }
+
+    fn write_file(&mut self) {
+        let file = BuildsFile { builds: self.builds.clone() };
+        write_file(self.path.as_deref(), &file, &mut self.logs);
+    }
+
+    fn apply_selected(&mut self) {
+        let Some(build) = self.selected.and_then(|idx| self.builds.get(idx)) else {
+            return;
+        };
+
+        let Some(mut stats) = self.ptr.read() else {
+            self.logs.push("Couldn't read character stats".to_string());
+            return;
+        };
+
+        build.apply(&mut stats, self.auto_level);
+        self.logs.push(format!("Applied \"{}\" (level {})", build.name, stats.level));
+        self.ptr.write(stats);
+    }
+
+    fn capture_current(&mut self) {
+        let Some(stats) = self.ptr.read() else {
+            self.logs.push("Couldn't read character stats".to_string());
+            return;
+        };
+
+        let name = match self.new_name.trim() {
+            "" => format!("Build {}", self.builds.len() + 1),
+            name => name.to_string(),
+        };
+
+        self.logs.push(format!("Captured \"{name}\""));
+        self.builds.push(BuildPreset::capture(name, &stats));
+        self.selected = Some(self.builds.len() - 1);
+        self.new_name.clear();
+        self.write_file();
+    }
+
+    fn delete_selected(&mut self) {
+        if let Some(idx) = self.selected.take().filter(|&idx| idx < self.builds.len()) {
+            let build = self.builds.remove(idx);
+            self.logs.push(format!("Deleted \"{}\"", build.name));
+            self.write_file();
+        }
+    }
+}
+
+impl Widget for BuildPresets {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            let _tok = ui.push_item_width(-1.);
+
+            ui.child_window("##build-presets-list").size([400., 200.]).build(|| {
+                for (idx, build) in self.builds.iter().enumerate() {
+                    let label = format!(
+                        "{}: {}/{}/{}/{}/{}/{}/{}/{}##{idx}",
+                        build.name,
+                        build.vigor,
+                        build.mind,
+                        build.endurance,
+                        build.strength,
+                        build.dexterity,
+                        build.intelligence,
+                        build.faith,
+                        build.arcane,
+                    );
+
+                    if ui.selectable_config(&label).selected(self.selected == Some(idx)).build() {
+                        self.selected = Some(idx);
+                    }
+                }
+            });
+
+            ui.checkbox("Compute level from attributes", &mut self.auto_level);
+
+            if ui.button_with_size("Apply", [196., button_height]) {
+                self.apply_selected();
+            }
+            ui.same_line();
+            if ui.button_with_size("Delete", [196., button_height]) {
+                self.delete_selected();
+            }
+
+            InputText::new(ui, "##build-presets-name", &mut self.new_name).hint("Name...").build();
+
+            if ui.button_with_size("Capture current stats", [400., button_height]) {
+                self.capture_current();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// `capture` takes an optional name, `apply` and `delete` the name of a
+/// build: `{ "name": "Strength 60" }`.
+impl Control for BuildPresets {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["apply", "capture", "delete"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "capture" {
+            self.new_name = arg::<Option<String>>(args, "name")?.unwrap_or_default();
+            self.capture_current();
+            return Ok(());
+        }
+
+        self.selected = Some(select(&self.builds, |b| &b.name, args)?);
+        match action {
+            "apply" => self.apply_selected(),
+            _ => self.delete_selected(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({ "builds": self.builds.iter().map(|b| &b.name).collect::<Vec<_>>() })
+    }
+}
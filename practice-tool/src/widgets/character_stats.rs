@@ -1,28 +1,98 @@
+use std::sync::Arc;
+
 use libeldenring::prelude::*;
+use parking_lot::Mutex;
+use practice_tool_core::crossbeam_channel::Sender;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::stats_editor::{Datum, Stats, StatsEditor};
-use practice_tool_core::widgets::Widget;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::{json, Value};
+
+use crate::remote::{arg, Control};
 
 #[derive(Debug)]
 struct CharacterStatsEdit {
     ptr: PointerChain<CharacterStats>,
+    origin_class: PointerChain<u8>,
     stats: Option<CharacterStats>,
+    class_id: i32,
+    // Base attributes of `class_id`'s class, used as the attribute minimums.
+    bases: Option<(i32, [i32; 8])>,
+    // Datum labels carrying the values that can't be edited: the class name,
+    // the level and the runes to the next level.
+    class_label: String,
+    souls_label: String,
+    logs: Arc<Mutex<Vec<String>>>,
+}
+
+impl CharacterStatsEdit {
+    fn bases(&mut self) -> [i32; 8] {
+        match self.bases {
+            Some((class_id, bases)) if class_id == self.class_id => bases,
+            _ => {
+                let bases = StartingClass::from_params(self.class_id as u8)
+                    .map(|c| c.attributes)
+                    .unwrap_or([1; 8]);
+                self.bases = Some((self.class_id, bases));
+                bases
+            },
+        }
+    }
+
+    /// Sets every attribute to the class base, which also brings the level
+    /// back to the class's starting level.
+    fn reset(&mut self) -> Result<(), String> {
+        self.read();
+        let bases = self.bases();
+        let stats = self.stats.as_mut().ok_or("Character stats can't be read right now")?;
+        stats.set_attributes(bases);
+        self.write();
+        self.logs
+            .lock()
+            .push(format!("Attributes reset to the {} base", class_name(self.class_id)));
+        Ok(())
+    }
+}
+
+fn class_name(class_id: i32) -> &'static str {
+    usize::try_from(class_id)
+        .ok()
+        .and_then(|class_id| STARTING_CLASSES.get(class_id))
+        .map(|class| class.name)
+        .unwrap_or("Unknown class")
 }
 
 impl Stats for CharacterStatsEdit {
     fn data(&mut self) -> Option<impl Iterator<Item = Datum>> {
+        let bases = self.bases();
+        self.class_label = format!("Origin class: {}###Origin class", class_name(self.class_id));
+
         self.stats.as_mut().map(|s| {
+            // Level always follows the attributes.
+            s.level = level_from_attributes(&s.attributes()).max(1);
+            self.souls_label = format!(
+                "Souls (level {}, {} to next)###Souls",
+                s.level,
+                runes_for_next_level(s.level)
+            );
+            let [vig, min, end, str, dex, int, fai, arc] = bases;
+
             [
-                Datum::int("Level", &mut s.level, 1, i32::MAX),
-                Datum::int("Vigor", &mut s.vigor, 1, 99),
-                Datum::int("Mind", &mut s.mind, 1, 99),
-                Datum::int("Endurance", &mut s.endurance, 1, 99),
-                Datum::int("Strength", &mut s.strength, 1, 99),
-                Datum::int("Dexterity", &mut s.dexterity, 1, 99),
-                Datum::int("Intelligence", &mut s.intelligence, 1, 99),
-                Datum::int("Faith", &mut s.faith, 1, 99),
-                Datum::int("Arcane", &mut s.arcane, 1, 99),
-                Datum::int("Souls", &mut s.runes, 1, i32::MAX),
+                Datum::int(
+                    &self.class_label,
+                    &mut self.class_id,
+                    0,
+                    STARTING_CLASSES.len() as i32 - 1,
+                ),
+                Datum::int("Vigor", &mut s.vigor, vig, 99),
+                Datum::int("Mind", &mut s.mind, min, 99),
+                Datum::int("Endurance", &mut s.endurance, end, 99),
+                Datum::int("Strength", &mut s.strength, str, 99),
+                Datum::int("Dexterity", &mut s.dexterity, dex, 99),
+                Datum::int("Intelligence", &mut s.intelligence, int, 99),
+                Datum::int("Faith", &mut s.faith, fai, 99),
+                Datum::int("Arcane", &mut s.arcane, arc, 99),
+                Datum::int(&self.souls_label, &mut s.runes, 1, i32::MAX),
             ]
             .into_iter()
         })
@@ -30,11 +100,29 @@ impl Stats for CharacterStatsEdit {
 
     fn read(&mut self) {
         self.stats = self.ptr.read();
+        self.class_id = self.origin_class.read().map(i32::from).unwrap_or(0);
     }
 
     fn write(&mut self) {
-        if let Some(stats) = self.stats.clone() {
+        let bases = self.bases();
+
+        if let Some(mut stats) = self.stats.clone() {
+            // Switching to a class with higher bases raises the attributes.
+            let attributes = stats.attributes();
+            let clamped = std::array::from_fn(|i| attributes[i].clamp(bases[i], 99));
+            for ((name, old), new) in ATTRIBUTES.into_iter().zip(attributes).zip(clamped) {
+                if new > old {
+                    let class = class_name(self.class_id);
+                    self.logs.lock().push(format!("{name} raised to {new}, the {class} base"));
+                } else if new < old {
+                    self.logs.lock().push(format!("{name} lowered to {new}"));
+                }
+            }
+
+            stats.set_attributes(clamped);
+            stats.level = level_from_attributes(&stats.attributes()).max(1);
             self.ptr.write(stats);
+            self.origin_class.write(self.class_id as u8);
         }
     }
 
@@ -43,14 +131,135 @@ impl Stats for CharacterStatsEdit {
     }
 }
 
+/// Attribute names, in the order of `CharacterStats::attributes`. The remote
+/// `set` action takes them in lowercase.
+const ATTRIBUTES: [&str; 8] =
+    ["Vigor", "Mind", "Endurance", "Strength", "Dexterity", "Intelligence", "Faith", "Arcane"];
+
+/// The stats editor only holds the stats while it's open, so the reset button
+/// and remote actions go through a second edit over the same pointers.
+struct CharacterStatsCommand {
+    editor: StatsEditor<CharacterStatsEdit>,
+    edit: CharacterStatsEdit,
+    logs: Arc<Mutex<Vec<String>>>,
+}
+
+impl Widget for CharacterStatsCommand {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.editor.render(ui);
+
+        let scale = scaling_factor(ui);
+        if ui.button_with_size("Reset to class base", [BUTTON_WIDTH * scale, BUTTON_HEIGHT]) {
+            if let Err(e) = self.edit.reset() {
+                self.logs.lock().push(e);
+            }
+        }
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.editor.render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.editor.interact(ui);
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.lock().drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// `set` takes any of the attributes, the runes held and the origin class by
+/// name: `{ "vigor": 40, "runes": 10000, "origin_class": "Bandit" }`. `reset`
+/// sets the attributes to the class base.
+impl Control for CharacterStatsCommand {
+    fn label(&self) -> String {
+        "Character stats".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["set", "reset"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "reset" {
+            return self.edit.reset();
+        }
+
+        self.edit.read();
+        if let Some(class) = arg::<Option<&str>>(args, "origin_class")? {
+            let class_id = STARTING_CLASSES
+                .iter()
+                .position(|c| c.name.eq_ignore_ascii_case(class))
+                .ok_or_else(|| format!("No class \"{class}\""))?;
+            self.edit.class_id = class_id as i32;
+        }
+
+        let bases = self.edit.bases();
+        let stats = self.edit.stats.as_mut().ok_or("Character stats can't be read right now")?;
+        let mut attributes = stats.attributes();
+        for ((value, name), base) in attributes.iter_mut().zip(ATTRIBUTES).zip(bases) {
+            if let Some(new_value) = arg(args, &name.to_lowercase())? {
+                if !(base..=99).contains(&new_value) {
+                    return Err(format!("{name} must be between {base} and 99"));
+                }
+                *value = new_value;
+            }
+        }
+        stats.set_attributes(attributes);
+        if let Some(runes) = arg(args, "runes")? {
+            stats.runes = runes;
+        }
+
+        self.edit.write();
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        self.edit.read();
+        let Some(stats) = self.edit.stats.as_ref() else {
+            return Value::Null;
+        };
+
+        let mut state = ATTRIBUTES
+            .into_iter()
+            .zip(stats.attributes())
+            .map(|(name, value)| (name.to_lowercase(), json!(value)))
+            .collect::<serde_json::Map<_, _>>();
+        state.insert("level".to_string(), json!(stats.level));
+        state.insert("runes".to_string(), json!(stats.runes));
+        state.insert("origin_class".to_string(), json!(class_name(self.edit.class_id)));
+        Value::Object(state)
+    }
+}
+
 pub(crate) fn character_stats_edit(
     character_stats: PointerChain<CharacterStats>,
+    origin_class: PointerChain<u8>,
     key_open: Option<Key>,
     key_close: Key,
-) -> Box<dyn Widget> {
-    Box::new(StatsEditor::new(
-        CharacterStatsEdit { ptr: character_stats, stats: None },
-        key_open,
-        Some(key_close),
-    ))
+) -> impl Widget + Control {
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let edit = |ptr, origin_class| CharacterStatsEdit {
+        ptr,
+        origin_class,
+        stats: None,
+        class_id: 0,
+        bases: None,
+        class_label: String::new(),
+        souls_label: String::new(),
+        logs: Arc::clone(&logs),
+    };
+
+    CharacterStatsCommand {
+        editor: StatsEditor::new(
+            edit(character_stats.clone(), origin_class.clone()),
+            key_open,
+            Some(key_close),
+        ),
+        edit: edit(character_stats, origin_class),
+        logs,
+    }
 }
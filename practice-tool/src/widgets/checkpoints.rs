@@ -0,0 +1,393 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use imgui::InputText;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::warp::LocationWarp;
+use super::{begin_popup, close_button, load_file, popup_anchor, string_match, write_file};
+use crate::remote::{arg, select, Control};
+use crate::util::get_dll_relative_path;
+
+const POPUP_TAG: &str = "##checkpoints";
+
+/// Everything a checkpoint reads and writes.
+pub(crate) struct CheckpointPointers {
+    pub(crate) global_position: Position,
+    pub(crate) hp: PointerChain<[u32; 3]>,
+    pub(crate) fp: PointerChain<[u32; 3]>,
+    pub(crate) sp: PointerChain<[u32; 3]>,
+    pub(crate) runes: PointerChain<u32>,
+    pub(crate) character_stats: PointerChain<CharacterStats>,
+    pub(crate) animation_speed: PointerChain<f32>,
+    /// Debug flags by their config name.
+    pub(crate) flags: Vec<(&'static str, Bitflag<u8>)>,
+}
+
+/// A snapshot of the player's state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Checkpoint {
+    name: String,
+    map_id: MapId,
+    position: [f32; 3],
+    rotation: [f32; 2],
+    hp: u32,
+    fp: u32,
+    sp: u32,
+    runes: u32,
+    level: i32,
+    attributes: [i32; 8],
+    speed: f32,
+    #[serde(default)]
+    flags: BTreeMap<String, bool>,
+}
+
+impl Checkpoint {
+    fn capture(name: String, ptrs: &CheckpointPointers) -> Option<Checkpoint> {
+        let [x, y, z, r1, r2] = ptrs.global_position.read()?;
+        let map_id = MapId(ptrs.global_position.read_map_id()?);
+        let [hp, ..] = ptrs.hp.read()?;
+        let [fp, ..] = ptrs.fp.read()?;
+        let [sp, ..] = ptrs.sp.read()?;
+        let stats = ptrs.character_stats.read()?;
+
+        let flags = ptrs
+            .flags
+            .iter()
+            .filter_map(|(name, flag)| Some((name.to_string(), flag.get()?)))
+            .collect();
+
+        Some(Checkpoint {
+            name,
+            map_id,
+            position: [x, y, z],
+            rotation: [r1, r2],
+            hp,
+            fp,
+            sp,
+            runes: ptrs.runes.read()?,
+            level: stats.level,
+            attributes: stats.attributes(),
+            speed: ptrs.animation_speed.read().unwrap_or(1.),
+            flags,
+        })
+    }
+
+    fn position(&self) -> [f32; 5] {
+        let [x, y, z] = self.position;
+        let [r1, r2] = self.rotation;
+        [x, y, z, r1, r2]
+    }
+
+    /// Writes everything but the position. Vitals are clamped to the current
+    /// maximums, which the game only recomputes from the stats later on.
+    fn apply(&self, ptrs: &CheckpointPointers) {
+        let flags: HashMap<_, _> = ptrs.flags.iter().map(|(name, flag)| (*name, flag)).collect();
+        for (name, state) in &self.flags {
+            if let Some(flag) = flags.get(name.as_str()) {
+                flag.set(*state);
+            }
+        }
+
+        if let Some(mut stats) = ptrs.character_stats.read() {
+            stats.set_attributes(self.attributes);
+            stats.level = self.level;
+            ptrs.character_stats.write(stats);
+        }
+        ptrs.runes.write(self.runes);
+        ptrs.animation_speed.write(self.speed);
+
+        for (vital, value) in [(&ptrs.hp, self.hp), (&ptrs.fp, self.fp), (&ptrs.sp, self.sp)] {
+            if let Some([_, base_max, max]) = vital.read() {
+                vital.write([value.min(max), base_max, max]);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CheckpointFile {
+    #[serde(default)]
+    checkpoints: Vec<Checkpoint>,
+}
+
+/// Named snapshots of the player's position, vitals, runes, stats, speed and
+/// debug flags, persisted to `checkpoints.toml` next to the DLL.
+pub(crate) struct Checkpoints {
+    label: String,
+    label_close: String,
+    hotkey_save: Option<Key>,
+    hotkey_close: Key,
+    hotkeys: Vec<(String, Key)>,
+
+    ptrs: CheckpointPointers,
+    warp: LocationWarp,
+    pending: Option<Checkpoint>,
+
+    path: Option<PathBuf>,
+    checkpoints: Vec<Checkpoint>,
+    selected: Option<usize>,
+    filter_string: String,
+    new_name: String,
+
+    logs: Vec<String>,
+}
+
+impl Checkpoints {
+    pub(crate) fn new(
+        ptrs: CheckpointPointers,
+        warp: LocationWarp,
+        hotkeys: BTreeMap<String, Key>,
+        hotkey_save: Option<Key>,
+        hotkey_close: Key,
+    ) -> Self {
+        let label = match &hotkey_save {
+            Some(k) => format!("Checkpoints ({k})"),
+            None => "Checkpoints".to_string(),
+        };
+
+        let path = get_dll_relative_path("checkpoints.toml");
+
+        let mut logs = Vec::new();
+        let checkpoints = load_file::<CheckpointFile>(path.as_deref(), &mut logs).checkpoints;
+
+        Checkpoints {
+            label,
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_save,
+            hotkey_close,
+            hotkeys: hotkeys.into_iter().collect(),
+            ptrs,
+            warp,
+            pending: None,
+            path,
+            checkpoints,
+            selected: None,
+            filter_string: String::new(),
+            new_name: String::new(),
+            logs,
+        }
+    }
+
+    fn write_file(&mut self) {
+        let file = CheckpointFile { checkpoints: self.checkpoints.clone() };
+        write_file(self.path.as_deref(), &file, &mut self.logs);
+    }
+
+    fn save_current(&mut self) {
+        let name = match self.new_name.trim() {
+            "" => format!("Checkpoint {}", self.checkpoints.len() + 1),
+            name => name.to_string(),
+        };
+
+        let Some(checkpoint) = Checkpoint::capture(name, &self.ptrs) else {
+            self.logs.push("Couldn't read the player's state".to_string());
+            return;
+        };
+
+        self.logs.push(format!("Saved checkpoint \"{}\"", checkpoint.name));
+        match self.checkpoints.iter().position(|c| c.name == checkpoint.name) {
+            Some(idx) => {
+                self.checkpoints[idx] = checkpoint;
+                self.selected = Some(idx);
+            },
+            None => {
+                self.checkpoints.push(checkpoint);
+                self.selected = Some(self.checkpoints.len() - 1);
+            },
+        }
+        self.new_name.clear();
+        self.write_file();
+    }
+
+    /// Moves the player to the checkpoint, then applies the rest of the state
+    /// once the map is loaded.
+    fn load(&mut self, idx: usize) {
+        let Some(checkpoint) = self.checkpoints.get(idx).cloned() else {
+            return;
+        };
+
+        self.warp.warp_to(checkpoint.map_id, checkpoint.position());
+        self.logs.push(format!("Loading checkpoint \"{}\"", checkpoint.name));
+        self.pending = Some(checkpoint);
+    }
+
+    fn delete_selected(&mut self) {
+        if let Some(idx) = self.selected.take().filter(|&idx| idx < self.checkpoints.len()) {
+            let checkpoint = self.checkpoints.remove(idx);
+            self.logs.push(format!("Deleted checkpoint \"{}\"", checkpoint.name));
+            self.write_file();
+        }
+    }
+}
+
+impl Widget for Checkpoints {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            let _tok = ui.push_item_width(-1.);
+
+            InputText::new(ui, "##checkpoints-filter", &mut self.filter_string)
+                .hint("Filter...")
+                .build();
+
+            ui.child_window("##checkpoints-list").size([400., 200.]).build(|| {
+                for (idx, checkpoint) in self.checkpoints.iter().enumerate() {
+                    if !string_match(&self.filter_string, &checkpoint.name) {
+                        continue;
+                    }
+
+                    let label = format!("{} ({})##{idx}", checkpoint.name, checkpoint.map_id);
+                    if ui.selectable_config(&label).selected(self.selected == Some(idx)).build() {
+                        self.selected = Some(idx);
+                    }
+                }
+            });
+
+            InputText::new(ui, "##checkpoints-name", &mut self.new_name).hint("Name...").build();
+
+            if ui.button_with_size("Save checkpoint", [400., button_height]) {
+                self.save_current();
+            }
+
+            let half_width = 196.;
+            if ui.button_with_size("Load", [half_width, button_height]) {
+                if let Some(idx) = self.selected {
+                    self.load(idx);
+                }
+            }
+            ui.same_line();
+            if ui.button_with_size("Delete", [half_width, button_height]) {
+                self.delete_selected();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.warp.update();
+
+        if !self.warp.is_pending() {
+            if let Some(checkpoint) = self.pending.take() {
+                checkpoint.apply(&self.ptrs);
+            }
+        }
+
+        if ui.is_any_item_active() {
+            return;
+        }
+
+        if self.hotkey_save.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.save_current();
+        }
+
+        let pressed = self
+            .hotkeys
+            .iter()
+            .filter(|(_, key)| key.is_pressed(ui))
+            .find_map(|(name, _)| self.checkpoints.iter().position(|c| &c.name == name));
+        if let Some(idx) = pressed {
+            self.load(idx);
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.warp.log(&tx);
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// `save` takes an optional name, `load` and `delete` the name of a
+/// checkpoint: `{ "name": "Margit" }`.
+impl Control for Checkpoints {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["save", "load", "delete"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "save" {
+            self.new_name = arg::<Option<String>>(args, "name")?.unwrap_or_default();
+            self.save_current();
+            return Ok(());
+        }
+
+        let idx = select(&self.checkpoints, |c| &c.name, args)?;
+        self.selected = Some(idx);
+        match action {
+            "load" => self.load(idx),
+            _ => self.delete_selected(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({
+            "checkpoints": self.checkpoints.iter().map(|c| &c.name).collect::<Vec<_>>(),
+            "pending": self.pending.as_ref().map(|c| &c.name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{load_toml, write_toml};
+
+    #[test]
+    fn test_checkpoint_file() {
+        let file = toml::from_str::<CheckpointFile>(
+            r#"
+            [[checkpoints]]
+            name = "Margit"
+            map_id = "m10_00_00_00"
+            position = [-43.5, 61.2, -130.0]
+            rotation = [1.5, 0.0]
+            hp = 800
+            fp = 90
+            sp = 100
+            runes = 12000
+            level = 30
+            attributes = [20, 10, 15, 14, 16, 9, 8, 9]
+            speed = 1.0
+
+            [checkpoints.flags]
+            no_damage = true
+            no_dead = false
+            "#,
+        )
+        .unwrap();
+
+        let checkpoint = &file.checkpoints[0];
+        assert_eq!(checkpoint.map_id, MapId::new(10, 0, 0, 0));
+        assert_eq!(checkpoint.position(), [-43.5, 61.2, -130.0, 1.5, 0.0]);
+        assert_eq!(checkpoint.flags.get("no_damage"), Some(&true));
+        assert_eq!(checkpoint.flags.get("no_dead"), Some(&false));
+
+        let path = std::env::temp_dir().join(format!("checkpoints-{}.toml", std::process::id()));
+        write_toml(&path, &file).unwrap();
+        let loaded = load_toml::<CheckpointFile>(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.checkpoints, file.checkpoints);
+    }
+}
@@ -3,11 +3,13 @@ use std::fmt::Write;
 
 use libeldenring::prelude::*;
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::store_value::{ReadWrite, StoreValue};
+use practice_tool_core::widgets::store_value::ReadWrite;
 use practice_tool_core::widgets::Widget;
 
+use crate::remote::{Control, ValueCommand};
+
 #[derive(Debug)]
-struct CycleSpeed {
+pub(crate) struct CycleSpeed {
     ptr: [PointerChain<f32>; 2],
     values: Vec<f32>,
     current: Option<f32>,
@@ -15,7 +17,7 @@ struct CycleSpeed {
 }
 
 impl CycleSpeed {
-    fn new(values: &[f32], ptr: [PointerChain<f32>; 2]) -> Self {
+    pub(crate) fn new(values: &[f32], ptr: [PointerChain<f32>; 2]) -> Self {
         let mut values = values.to_vec();
         values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
         CycleSpeed { ptr, values, current: None, label: String::new() }
@@ -55,6 +57,6 @@ pub(crate) fn cycle_speed(
     values: &[f32],
     ptr: [PointerChain<f32>; 2],
     key: Option<Key>,
-) -> Box<dyn Widget> {
-    Box::new(StoreValue::new(CycleSpeed::new(values, ptr), key))
+) -> impl Widget + Control {
+    ValueCommand::new(CycleSpeed::new(values, ptr), key)
 }
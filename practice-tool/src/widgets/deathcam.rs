@@ -1,8 +1,10 @@
 use libeldenring::memedit::{Bitflag, PointerChain};
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::flag::{Flag, FlagWidget};
+use practice_tool_core::widgets::flag::Flag;
 use practice_tool_core::widgets::Widget;
 
+use crate::remote::{Control, FlagCommand};
+
 #[derive(Debug)]
 pub(crate) struct Deathcam {
     flag: Bitflag<u8>,
@@ -39,6 +41,6 @@ pub(crate) fn deathcam(
     flag_torrent: Bitflag<u8>,
     seven: PointerChain<u8>,
     key: Option<Key>,
-) -> Box<dyn Widget> {
-    Box::new(FlagWidget::new("Deathcam", Deathcam::new(flag, flag_torrent, seven), key))
+) -> impl Widget + Control {
+    FlagCommand::new("Deathcam", Deathcam::new(flag, flag_torrent, seven), key)
 }
@@ -0,0 +1,132 @@
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::flag::Flag;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::Value;
+
+use super::{begin_popup, close_button, popup_anchor};
+use crate::remote::{arg, Control, FlagCommand};
+
+const POPUP_TAG: &str = "##event-flags";
+
+pub(crate) struct EventFlag {
+    event_flags: EventFlags,
+    flag_id: u32,
+}
+
+impl EventFlag {
+    pub(crate) fn new(event_flags: EventFlags, flag_id: u32) -> Self {
+        EventFlag { event_flags, flag_id }
+    }
+}
+
+impl Flag for EventFlag {
+    fn set(&mut self, value: bool) {
+        self.event_flags.set_flag(self.flag_id, value);
+    }
+
+    fn get(&self) -> Option<bool> {
+        self.event_flags.get_flag(self.flag_id)
+    }
+}
+
+/// Toggles a single event flag, named in the config.
+pub(crate) fn event_flag_widget(
+    label: &str,
+    event_flags: EventFlags,
+    flag_id: u32,
+    key: Option<Key>,
+) -> impl Widget + Control {
+    FlagCommand::new(label, EventFlag::new(event_flags, flag_id), key)
+}
+
+/// Reads and writes arbitrary event flags by id.
+pub(crate) struct EventFlagEditor {
+    label_close: String,
+    hotkey_close: Key,
+
+    event_flags: EventFlags,
+    flag_id: i32,
+
+    logs: Vec<String>,
+}
+
+impl EventFlagEditor {
+    pub(crate) fn new(event_flags: EventFlags, hotkey_close: Key) -> Self {
+        EventFlagEditor {
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            event_flags,
+            flag_id: 0,
+            logs: Vec::new(),
+        }
+    }
+
+    fn set(&mut self, value: bool) {
+        let flag_id = self.flag_id.max(0) as u32;
+        match self.event_flags.set_flag(flag_id, value) {
+            Some(()) => self.logs.push(format!("Event flag {flag_id} set to {value}")),
+            None => self.logs.push(format!("Event flag {flag_id} not available")),
+        }
+    }
+}
+
+impl Widget for EventFlagEditor {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size("Event flags", [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            ui.set_next_item_width(150.);
+            ui.input_int("Flag id", &mut self.flag_id).build();
+
+            match self.event_flags.get_flag(self.flag_id.max(0) as u32) {
+                Some(state) => ui.text(format!("State: {}", if state { "on" } else { "off" })),
+                None => ui.text("State: not available"),
+            }
+
+            if ui.button_with_size("Set", [146., button_height]) {
+                self.set(true);
+            }
+            ui.same_line();
+            if ui.button_with_size("Clear", [146., button_height]) {
+                self.set(false);
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 300.);
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// Actions take the flag id: `{ "flag_id": 710520 }`.
+impl Control for EventFlagEditor {
+    fn label(&self) -> String {
+        "Event flags".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["set", "clear"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        let flag_id: u32 = arg(args, "flag_id")?;
+        self.event_flags.get_flag(flag_id).ok_or(format!("Event flag {flag_id} not available"))?;
+        self.flag_id = i32::try_from(flag_id).map_err(|e| e.to_string())?;
+        self.set(action == "set");
+        Ok(())
+    }
+}
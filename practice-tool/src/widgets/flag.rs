@@ -1,8 +1,10 @@
 use libeldenring::memedit::Bitflag as BitflagInner;
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::flag::{Flag, FlagWidget};
+use practice_tool_core::widgets::flag::Flag;
 use practice_tool_core::widgets::Widget;
 
+use crate::remote::{Control, FlagCommand};
+
 struct Bitflag(BitflagInner<u8>);
 
 impl Flag for Bitflag {
@@ -19,6 +21,6 @@ pub(crate) fn flag_widget(
     label: &str,
     bitflag: BitflagInner<u8>,
     key: Option<Key>,
-) -> Box<dyn Widget> {
-    Box::new(FlagWidget::new(label, Bitflag(bitflag), key))
+) -> impl Widget + Control {
+    FlagCommand::new(label, Bitflag(bitflag), key)
 }
@@ -0,0 +1,183 @@
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::{json, Value};
+
+use super::{begin_popup, close_button, popup_anchor};
+use crate::remote::{arg, Control};
+
+const POPUP_TAG: &str = "##flasks-blessings";
+
+/// Flasks that can be allocated between Crimson and Cerulean in total.
+const MAX_FLASKS: i32 = 14;
+const MAX_SCADUTREE_BLESSING: i32 = 20;
+const MAX_REVERED_SPIRIT_ASH: i32 = 10;
+
+struct Upgrade {
+    label: &'static str,
+    ptr: PointerChain<u8>,
+    max: i32,
+    value: i32,
+    available: bool,
+}
+
+impl Upgrade {
+    fn new(label: &'static str, ptr: PointerChain<u8>, max: i32) -> Self {
+        Upgrade { label, ptr, max, value: 0, available: false }
+    }
+
+    /// Reads the current value, unless it's being edited.
+    fn read(&mut self, ui: &imgui::Ui) {
+        let current = self.ptr.read();
+        self.available = current.is_some();
+        if let Some(current) = current.filter(|_| !ui.is_any_item_active()) {
+            self.value = current as i32;
+        }
+    }
+
+    fn render(&mut self, ui: &imgui::Ui) {
+        if !self.available {
+            ui.text(format!("{}: not available", self.label));
+            return;
+        }
+
+        if ui.input_int(self.label, &mut self.value).build() {
+            self.value = self.value.clamp(0, self.max);
+            self.ptr.write(self.value as u8);
+        }
+    }
+}
+
+/// Edits the Crimson/Cerulean flask allocation, the Great Rune activation
+/// and, on DLC versions, the Scadutree Blessing and Revered Spirit Ash levels.
+pub(crate) struct FlasksBlessings {
+    label_close: String,
+    hotkey_close: Key,
+
+    crimson: Upgrade,
+    cerulean: Upgrade,
+    great_rune_active: Bitflag<u8>,
+    blessings: Vec<Upgrade>,
+}
+
+impl FlasksBlessings {
+    pub(crate) fn new(
+        crimson_flasks: PointerChain<u8>,
+        cerulean_flasks: PointerChain<u8>,
+        great_rune_active: Bitflag<u8>,
+        scadutree_blessing: Option<PointerChain<u8>>,
+        revered_spirit_ash: Option<PointerChain<u8>>,
+        hotkey_close: Key,
+    ) -> Self {
+        let blessings = [
+            ("Scadutree Blessing", scadutree_blessing, MAX_SCADUTREE_BLESSING),
+            ("Revered Spirit Ash", revered_spirit_ash, MAX_REVERED_SPIRIT_ASH),
+        ]
+        .into_iter()
+        .filter_map(|(label, ptr, max)| ptr.map(|ptr| Upgrade::new(label, ptr, max)))
+        .collect();
+
+        FlasksBlessings {
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            crimson: Upgrade::new("Crimson flasks", crimson_flasks, MAX_FLASKS),
+            cerulean: Upgrade::new("Cerulean flasks", cerulean_flasks, MAX_FLASKS),
+            great_rune_active,
+            blessings,
+        }
+    }
+}
+
+impl Widget for FlasksBlessings {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size("Flasks & blessings", [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            {
+                let _tok = ui.push_item_width(150.);
+
+                // Both flask counts are read before either is clamped against
+                // the other.
+                self.crimson.read(ui);
+                self.cerulean.read(ui);
+
+                self.crimson.max = MAX_FLASKS - self.cerulean.value;
+                self.crimson.render(ui);
+                self.cerulean.max = MAX_FLASKS - self.crimson.value;
+                self.cerulean.render(ui);
+
+                for blessing in self.blessings.iter_mut() {
+                    blessing.read(ui);
+                    blessing.render(ui);
+                }
+            }
+
+            match self.great_rune_active.get() {
+                Some(mut active) => {
+                    if ui.checkbox("Great Rune active", &mut active) {
+                        self.great_rune_active.set(active);
+                    }
+                },
+                None => ui.text("Great Rune: not available"),
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 300.);
+        }
+    }
+}
+
+/// `set` takes an upgrade and a value, `{ "name": "Crimson flasks",
+/// "value": 8 }`, and `great_rune` takes `{ "active": true }`.
+impl Control for FlasksBlessings {
+    fn label(&self) -> String {
+        "Flasks & blessings".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["set", "great_rune"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "great_rune" {
+            let active = arg(args, "active")?;
+            self.great_rune_active.get().ok_or("The Great Rune can't be read right now")?;
+            self.great_rune_active.set(active);
+            return Ok(());
+        }
+
+        let name: &str = arg(args, "name")?;
+        let value: i32 = arg(args, "value")?;
+        let flasks = |upgrade: &Upgrade| upgrade.ptr.read().map(i32::from).unwrap_or(0);
+
+        let (upgrade, max) = if name == self.crimson.label {
+            (&self.crimson, MAX_FLASKS - flasks(&self.cerulean))
+        } else if name == self.cerulean.label {
+            (&self.cerulean, MAX_FLASKS - flasks(&self.crimson))
+        } else {
+            let blessing = self.blessings.iter().find(|b| b.label == name);
+            blessing.map(|b| (b, b.max)).ok_or(format!("No entry \"{name}\""))?
+        };
+
+        upgrade.ptr.read().ok_or(format!("{name} can't be read right now"))?;
+        upgrade.ptr.write(value.clamp(0, max) as u8);
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        let mut state = [&self.crimson, &self.cerulean]
+            .into_iter()
+            .chain(&self.blessings)
+            .map(|u| (u.label.to_string(), json!(u.ptr.read())))
+            .collect::<serde_json::Map<_, _>>();
+        state.insert("Great Rune active".to_string(), json!(self.great_rune_active.get()));
+        Value::Object(state)
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::BTreeMap;
+
+use imgui::InputText;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::{json, Value};
+
+use super::item_spawn::ItemSpawnInstance;
+use super::{begin_popup, close_button, popup_anchor, string_match};
+use crate::remote::{arg, Control};
+
+const POPUP_TAG: &str = "##inventory";
+
+/// Total quantity held of each item id.
+type Snapshot = BTreeMap<u32, u32>;
+
+#[derive(Debug, PartialEq, Eq)]
+enum RestoreAction {
+    SetQuantity { slot: usize, quantity: u32 },
+    Remove { slot: usize },
+    Spawn { item_id: u32, quantity: u32 },
+}
+
+fn take_snapshot(items: &[(usize, InventoryItem)]) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    for (_, item) in items {
+        *snapshot.entry(item.item_id).or_default() += item.quantity;
+    }
+    snapshot
+}
+
+fn is_stackable(item_id: u32) -> bool {
+    ItemCategory::from_item_id(item_id).map(|(c, _)| c.is_stackable()).unwrap_or(false)
+}
+
+/// Edits needed to bring the inventory back to the snapshot. Stackable items
+/// are collapsed into their first slot; other items are removed or spawned
+/// one by one until the count matches.
+fn restore_plan(
+    items: &[(usize, InventoryItem)],
+    snapshot: &Snapshot,
+    stackable: impl Fn(u32) -> bool,
+) -> Vec<RestoreAction> {
+    let mut current: BTreeMap<u32, Vec<(usize, u32)>> = BTreeMap::new();
+    for (slot, item) in items {
+        current.entry(item.item_id).or_default().push((*slot, item.quantity));
+    }
+
+    let mut item_ids: Vec<u32> = current.keys().chain(snapshot.keys()).copied().collect();
+    item_ids.sort();
+    item_ids.dedup();
+
+    let mut actions = Vec::new();
+    for item_id in item_ids {
+        let target = snapshot.get(&item_id).copied().unwrap_or(0);
+        let slots = current.get(&item_id).map(Vec::as_slice).unwrap_or(&[]);
+
+        if stackable(item_id) {
+            match slots.split_first() {
+                None if target > 0 => {
+                    actions.push(RestoreAction::Spawn { item_id, quantity: target })
+                },
+                None => {},
+                Some((&(slot, _), rest)) => {
+                    let rest = if target == 0 { slots } else { rest };
+                    if target > 0 && slots[0].1 != target {
+                        actions.push(RestoreAction::SetQuantity { slot, quantity: target });
+                    }
+                    actions.extend(rest.iter().map(|&(slot, _)| RestoreAction::Remove { slot }));
+                },
+            }
+        } else {
+            let target = target as usize;
+            if slots.len() > target {
+                actions.extend(
+                    slots[target..].iter().map(|&(slot, _)| RestoreAction::Remove { slot }),
+                );
+            } else if slots.len() < target {
+                actions.push(RestoreAction::Spawn {
+                    item_id,
+                    quantity: (target - slots.len()) as u32,
+                });
+            }
+        }
+    }
+
+    actions
+}
+
+/// Lists the player's inventory, edits quantities and restores it to a
+/// snapshot taken earlier.
+pub(crate) struct InventoryEditor {
+    label: String,
+    label_close: String,
+    hotkey_restore: Option<Key>,
+    hotkey_close: Key,
+
+    inventory: Inventory,
+    func_ptr: usize,
+    map_item_man: usize,
+    sentinel: Bitflag<u8>,
+
+    entries: Vec<InventoryEntry>,
+    filter_string: String,
+    category: usize,
+    selected: Option<usize>,
+    quantity: i32,
+    snapshot: Option<Snapshot>,
+
+    logs: Vec<String>,
+}
+
+impl InventoryEditor {
+    pub(crate) fn new(
+        inventory: Inventory,
+        func_ptr: usize,
+        map_item_man: usize,
+        sentinel: Bitflag<u8>,
+        hotkey_restore: Option<Key>,
+        hotkey_close: Key,
+    ) -> Self {
+        InventoryEditor {
+            label: "Inventory".to_string(),
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_restore,
+            hotkey_close,
+            inventory,
+            func_ptr,
+            map_item_man,
+            sentinel,
+            entries: Vec::new(),
+            filter_string: String::new(),
+            category: 0,
+            selected: None,
+            quantity: 1,
+            snapshot: None,
+            logs: Vec::new(),
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.entries = self.inventory.entries().unwrap_or_default();
+        if self.selected.is_some_and(|slot| !self.entries.iter().any(|e| e.slot == slot)) {
+            self.selected = None;
+        }
+    }
+
+    fn selected_entry(&self) -> Option<&InventoryEntry> {
+        self.selected.and_then(|slot| self.entries.iter().find(|e| e.slot == slot))
+    }
+
+    fn set_quantity(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+
+        let max = entry.max_quantity.unwrap_or(1).max(1);
+        let quantity = (self.quantity.max(1) as u32).min(max);
+        let (slot, name) = (entry.slot, entry.name.clone());
+
+        if self.inventory.is_equipped(entry.item.item_id) {
+            self.logs.push(format!("Not changing {name} while it's equipped"));
+            return;
+        }
+
+        if self.inventory.set_quantity(slot, quantity).is_some() {
+            self.logs.push(format!("Set {name} to {quantity}"));
+        }
+        self.refresh();
+    }
+
+    fn remove(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let (slot, name) = (entry.slot, entry.name.clone());
+
+        if self.inventory.is_equipped(entry.item.item_id) {
+            self.logs.push(format!("Not removing {name} while it's equipped"));
+            return;
+        }
+
+        if entry.item.has_ga_item() {
+            self.logs.push(format!("Not removing {name}, its GaItem entry can't be released"));
+            return;
+        }
+
+        if self.inventory.remove(slot).is_some() {
+            self.logs.push(format!("Removed {name}"));
+        }
+        self.selected = None;
+        self.refresh();
+    }
+
+    fn take_snapshot(&mut self) {
+        match self.inventory.items() {
+            Some(items) => {
+                self.logs.push(format!("Inventory snapshot taken ({} items)", items.len()));
+                self.snapshot = Some(take_snapshot(&items));
+            },
+            None => self.logs.push("Couldn't read inventory".to_string()),
+        }
+    }
+
+    fn restore_snapshot(&mut self) {
+        let Some(snapshot) = self.snapshot.as_ref() else {
+            self.logs.push("No inventory snapshot taken".to_string());
+            return;
+        };
+
+        if self.sentinel.get().is_none() {
+            self.logs.push("Not restoring inventory when not in game".to_string());
+            return;
+        }
+
+        let Some(items) = self.inventory.items() else {
+            self.logs.push("Couldn't read inventory".to_string());
+            return;
+        };
+
+        let actions = restore_plan(&items, snapshot, is_stackable);
+        let mut skipped = 0;
+        for action in &actions {
+            match *action {
+                RestoreAction::SetQuantity { slot, quantity } => {
+                    if self.inventory.set_quantity(slot, quantity).is_none() {
+                        skipped += 1;
+                    }
+                },
+                RestoreAction::Remove { slot } => {
+                    if self.inventory.remove(slot).is_none() {
+                        skipped += 1;
+                    }
+                },
+                RestoreAction::Spawn { item_id, quantity } => unsafe {
+                    ItemSpawnInstance {
+                        spawn_item_func_ptr: self.func_ptr as _,
+                        map_item_man: self.map_item_man as _,
+                        qty: quantity,
+                        item_id,
+                    }
+                    .spawn();
+                },
+            }
+        }
+
+        self.logs.push(format!("Inventory restored ({} changes)", actions.len() - skipped));
+        if skipped > 0 {
+            self.logs.push(format!("{skipped} equipped or unreleasable items were left alone"));
+        }
+        self.refresh();
+    }
+
+    fn render_list(&mut self, ui: &imgui::Ui) {
+        let category = self.category.checked_sub(1).map(|i| ItemCategory::ALL[i]);
+
+        ui.child_window("##inventory-list").size([400., 250.]).build(|| {
+            for entry in &self.entries {
+                if category.is_some_and(|c| c != entry.category)
+                    || !string_match(&self.filter_string, &entry.name)
+                {
+                    continue;
+                }
+
+                let label = format!("{} x{}##{}", entry.name, entry.item.quantity, entry.slot);
+                if ui.selectable_config(&label).selected(self.selected == Some(entry.slot)).build()
+                {
+                    self.selected = Some(entry.slot);
+                    self.quantity = entry.item.quantity as i32;
+                }
+            }
+        });
+    }
+}
+
+impl Widget for InventoryEditor {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            self.refresh();
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            {
+                let _tok = ui.push_item_width(-1.);
+                InputText::new(ui, "##inventory-filter", &mut self.filter_string)
+                    .hint("Filter...")
+                    .build();
+
+                let categories: Vec<&str> = std::iter::once("All categories")
+                    .chain(ItemCategory::ALL.iter().map(ItemCategory::label))
+                    .collect();
+                ui.combo_simple_string("##inventory-category", &mut self.category, &categories);
+            }
+
+            self.render_list(ui);
+
+            match self.selected_entry() {
+                Some(entry) => {
+                    let max = entry.max_quantity.unwrap_or(1).max(1) as i32;
+                    ui.text(format!("{} (max {max})", entry.name));
+
+                    ui.set_next_item_width(196.);
+                    if ui.input_int("##inventory-quantity", &mut self.quantity).build() {
+                        self.quantity = self.quantity.clamp(1, max);
+                    }
+                    ui.same_line();
+                    if ui.button_with_size("Set quantity", [196., button_height]) {
+                        self.set_quantity();
+                    }
+
+                    if ui.button_with_size("Remove", [400., button_height]) {
+                        self.remove();
+                    }
+                },
+                None => ui.text("No item selected"),
+            }
+
+            ui.separator();
+
+            if ui.button_with_size("Take snapshot", [196., button_height]) {
+                self.take_snapshot();
+            }
+            ui.same_line();
+            if ui.button_with_size("Restore snapshot", [196., button_height]) {
+                self.restore_snapshot();
+            }
+
+            if ui.button_with_size("Refresh", [400., button_height]) {
+                self.refresh();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if self.hotkey_restore.as_ref().map(|k| k.is_pressed(ui)).unwrap_or(false)
+            && !ui.is_any_item_active()
+        {
+            self.restore_snapshot();
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// `set_quantity` and `remove` take an inventory slot from the state:
+/// `{ "slot": 12, "quantity": 5 }`.
+impl Control for InventoryEditor {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["snapshot", "restore", "set_quantity", "remove"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        match action {
+            "snapshot" => self.take_snapshot(),
+            "restore" => self.restore_snapshot(),
+            _ => {
+                self.refresh();
+                let slot = arg(args, "slot")?;
+                if !self.entries.iter().any(|e| e.slot == slot) {
+                    return Err(format!("No item in slot {slot}"));
+                }
+                self.selected = Some(slot);
+                if action == "remove" {
+                    self.remove();
+                } else {
+                    self.quantity = arg(args, "quantity")?;
+                    self.set_quantity();
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        self.refresh();
+        let items = self
+            .entries
+            .iter()
+            .map(|e| json!({ "slot": e.slot, "name": e.name, "quantity": e.item.quantity }))
+            .collect::<Vec<_>>();
+        json!({ "items": items, "snapshot": self.snapshot.is_some() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOLUS: u32 = 0x40000000 + 900;
+    const DAGGER: u32 = 1000000;
+
+    fn item(item_id: u32, quantity: u32) -> InventoryItem {
+        let mut item = InventoryItem::default();
+        item.item_id = item_id;
+        item.quantity = quantity;
+        item
+    }
+
+    #[test]
+    fn test_restore_plan() {
+        let before = [(0, item(BOLUS, 5)), (1, item(DAGGER, 1))];
+        let snapshot = take_snapshot(&before);
+
+        assert_eq!(restore_plan(&before, &snapshot, is_stackable), vec![]);
+
+        let after = [(0, item(BOLUS, 99)), (1, item(DAGGER, 1)), (2, item(DAGGER, 1))];
+        assert_eq!(restore_plan(&after, &snapshot, is_stackable), vec![
+            RestoreAction::Remove { slot: 2 },
+            RestoreAction::SetQuantity { slot: 0, quantity: 5 },
+        ]);
+
+        let after = [(3, item(0x40000000 + 1000, 1))];
+        assert_eq!(restore_plan(&after, &snapshot, is_stackable), vec![
+            RestoreAction::Spawn { item_id: DAGGER, quantity: 1 },
+            RestoreAction::Spawn { item_id: BOLUS, quantity: 5 },
+            RestoreAction::Remove { slot: 3 },
+        ]);
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use hudhook::tracing::error;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::item_spawn::{encode_item_id, item_label, validate_quantity, ItemSpawnInstance};
+use super::{begin_popup, close_button, popup_anchor};
+use crate::remote::{arg, select, Control};
+use crate::util::get_dll_relative_path;
+
+const POPUP_TAG: &str = "##item-kits";
+
+/// Delay between two spawns of a kit or item lot.
+const SPAWN_DELAY: Duration = Duration::from_millis(150);
+
+/// `ItemLotParam` tables, in lookup order.
+const ITEM_LOT_PARAMS: [&str; 3] = ["ItemLotParam_map", "ItemLotParam_enemy", "ItemLotParam"];
+
+fn default_qty() -> u32 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+struct KitEntry {
+    id: u32,
+    #[serde(default)]
+    upgrade: u32,
+    affinity: Option<String>,
+    #[serde(default = "default_qty")]
+    qty: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct KitFile {
+    #[serde(default)]
+    items: Vec<KitEntry>,
+}
+
+/// A list of items to spawn, loaded from a `.toml` or `.json` file in the
+/// `kits` directory next to the DLL.
+#[derive(Debug)]
+struct Kit {
+    name: String,
+    items: Vec<(u32, u32)>,
+}
+
+impl Kit {
+    fn parse(name: String, content: &str, json: bool) -> Result<Kit, String> {
+        let file: KitFile = if json {
+            serde_json::from_str(content).map_err(|e| format!("Couldn't parse kit {name}: {e}"))?
+        } else {
+            toml::from_str(content).map_err(|e| format!("Couldn't parse kit {name}: {e}"))?
+        };
+
+        let items = file
+            .items
+            .into_iter()
+            .map(|e| encode_item_id(e.id, e.upgrade, e.affinity.as_deref()).map(|id| (id, e.qty)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid item in kit {name}: {e}"))?;
+
+        Ok(Kit { name, items })
+    }
+
+    fn load(path: &Path) -> Option<Result<Kit, String>> {
+        let json = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => false,
+            Some("json") => true,
+            _ => return None,
+        };
+        let name = path.file_stem()?.to_string_lossy().to_string();
+
+        Some(
+            std::fs::read_to_string(path)
+                .map_err(|e| format!("Couldn't read kit {name}: {e}"))
+                .and_then(|content| Kit::parse(name, &content, json)),
+        )
+    }
+}
+
+/// Spawnable entries of an `ItemLotParam` row: item id and quantity.
+fn item_lot(row_id: u64) -> Option<Vec<(u32, u32)>> {
+    let params = PARAMS.read();
+
+    let lot = ITEM_LOT_PARAMS.iter().find_map(|name| {
+        unsafe { params.iter_param::<ItemLotParam>(name) }?
+            .find(|p| p.id == row_id)
+            .and_then(|p| p.param)
+    })?;
+
+    let ids = [
+        lot.lot_item_id01,
+        lot.lot_item_id02,
+        lot.lot_item_id03,
+        lot.lot_item_id04,
+        lot.lot_item_id05,
+        lot.lot_item_id06,
+        lot.lot_item_id07,
+        lot.lot_item_id08,
+    ];
+    let categories = [
+        lot.lot_item_category01,
+        lot.lot_item_category02,
+        lot.lot_item_category03,
+        lot.lot_item_category04,
+        lot.lot_item_category05,
+        lot.lot_item_category06,
+        lot.lot_item_category07,
+        lot.lot_item_category08,
+    ];
+    let nums = [
+        lot.lot_item_num01,
+        lot.lot_item_num02,
+        lot.lot_item_num03,
+        lot.lot_item_num04,
+        lot.lot_item_num05,
+        lot.lot_item_num06,
+        lot.lot_item_num07,
+        lot.lot_item_num08,
+    ];
+
+    Some(
+        ids.into_iter()
+            .zip(categories)
+            .zip(nums)
+            .filter(|((id, _), _)| *id > 0)
+            .filter_map(|((id, category), num)| {
+                let category = match category {
+                    1 => ItemCategory::Goods,
+                    2 => ItemCategory::Weapon,
+                    3 => ItemCategory::Protector,
+                    4 => ItemCategory::Accessory,
+                    5 => ItemCategory::Gem,
+                    _ => return None,
+                };
+                Some((category.item_id(id as u32), (num as u32).max(1)))
+            })
+            .collect(),
+    )
+}
+
+/// Spawns lists of items from kit files or `ItemLotParam` rows, one at a time.
+pub(crate) struct ItemKits {
+    label: String,
+    label_close: String,
+    hotkey_close: Key,
+
+    func_ptr: usize,
+    map_item_man: usize,
+    sentinel: Bitflag<u8>,
+
+    path: Option<PathBuf>,
+    kits: Vec<Kit>,
+    selected: Option<usize>,
+    item_lot: i32,
+
+    queue: VecDeque<(u32, u32)>,
+    next_spawn: Instant,
+
+    logs: Vec<String>,
+}
+
+impl ItemKits {
+    pub(crate) fn new(
+        func_ptr: usize,
+        map_item_man: usize,
+        sentinel: Bitflag<u8>,
+        hotkey_close: Key,
+    ) -> Self {
+        let path = get_dll_relative_path("kits");
+
+        let mut kits = ItemKits {
+            label: "Item kits".to_string(),
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            func_ptr,
+            map_item_man,
+            sentinel,
+            path,
+            kits: Vec::new(),
+            selected: None,
+            item_lot: 0,
+            queue: VecDeque::new(),
+            next_spawn: Instant::now(),
+            logs: Vec::new(),
+        };
+        kits.reload();
+        kits
+    }
+
+    fn reload(&mut self) {
+        self.kits.clear();
+        self.selected = None;
+
+        let Some(entries) = self.path.as_ref().and_then(|path| std::fs::read_dir(path).ok()) else {
+            return;
+        };
+
+        let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        paths.sort();
+
+        for result in paths.iter().filter_map(|path| Kit::load(path)) {
+            match result {
+                Ok(kit) => self.kits.push(kit),
+                Err(e) => {
+                    error!("{e}");
+                    self.logs.push(e);
+                },
+            }
+        }
+    }
+
+    fn enqueue(&mut self, items: Vec<(u32, u32)>, source: String) {
+        if self.sentinel.get().is_none() {
+            self.logs.push("Not spawning items when not in game".to_string());
+            return;
+        }
+
+        // Same checks as the item spawner, so kits can't exceed what it allows.
+        let mut valid = Vec::new();
+        for (item_id, qty) in items {
+            match validate_quantity(item_id, qty) {
+                Ok(()) => valid.push((item_id, qty)),
+                Err(e) => self.logs.push(format!("Not spawning {}: {e}", item_label(item_id))),
+            }
+        }
+
+        self.logs.push(format!("Spawning {} items from {source}", valid.len()));
+        self.queue.extend(valid);
+    }
+
+    fn spawn_item_lot(&mut self) {
+        let row_id = self.item_lot.max(0) as u64;
+        match item_lot(row_id) {
+            Some(items) if !items.is_empty() => self.enqueue(items, format!("item lot {row_id}")),
+            Some(_) => self.logs.push(format!("Item lot {row_id} is empty")),
+            None => self.logs.push(format!("Item lot {row_id} not found")),
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.queue.is_empty() || Instant::now() < self.next_spawn {
+            return;
+        }
+
+        if self.sentinel.get().is_none() {
+            self.logs.push(format!("Left the game, {} items not spawned", self.queue.len()));
+            self.queue.clear();
+            return;
+        }
+
+        let Some((item_id, qty)) = self.queue.pop_front() else {
+            return;
+        };
+
+        let i = ItemSpawnInstance {
+            spawn_item_func_ptr: self.func_ptr as _,
+            map_item_man: self.map_item_man as _,
+            qty,
+            item_id,
+        };
+
+        self.logs.push(format!("Spawning {qty} {}", item_label(item_id)));
+
+        unsafe { i.spawn() };
+        self.next_spawn = Instant::now() + SPAWN_DELAY;
+    }
+}
+
+impl Widget for ItemKits {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            ui.child_window("##item-kits-list").size([400., 200.]).build(|| {
+                for (idx, kit) in self.kits.iter().enumerate() {
+                    let label = format!("{} ({} items)##{idx}", kit.name, kit.items.len());
+                    if ui.selectable_config(&label).selected(self.selected == Some(idx)).build() {
+                        self.selected = Some(idx);
+                    }
+                }
+            });
+
+            if ui.button_with_size("Spawn kit", [196., button_height]) {
+                if let Some(kit) = self.selected.and_then(|idx| self.kits.get(idx)) {
+                    let (items, name) = (kit.items.clone(), kit.name.clone());
+                    self.enqueue(items, name);
+                }
+            }
+            ui.same_line();
+            if ui.button_with_size("Reload kits", [196., button_height]) {
+                self.reload();
+            }
+
+            ui.separator();
+
+            ui.set_next_item_width(196.);
+            ui.input_int("##item-kits-lot", &mut self.item_lot).build();
+            ui.same_line();
+            if ui.button_with_size("Spawn item lot", [196., button_height]) {
+                self.spawn_item_lot();
+            }
+
+            if !self.queue.is_empty() {
+                ui.text(format!("{} items queued", self.queue.len()));
+                ui.same_line();
+                if ui.button("Cancel") {
+                    self.queue.clear();
+                }
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn interact(&mut self, _ui: &imgui::Ui) {
+        self.tick();
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// `spawn` takes the name of a kit, `{ "name": "Starter" }`, and `spawn_lot`
+/// an `ItemLotParam` row, `{ "row_id": 10000 }`.
+impl Control for ItemKits {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["spawn", "spawn_lot", "reload", "cancel"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        match action {
+            "spawn" => {
+                let kit = &self.kits[select(&self.kits, |k| &k.name, args)?];
+                let (items, name) = (kit.items.clone(), kit.name.clone());
+                self.enqueue(items, name);
+            },
+            "spawn_lot" => {
+                self.item_lot = arg(args, "row_id")?;
+                self.spawn_item_lot();
+            },
+            "reload" => self.reload(),
+            _ => self.queue.clear(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({
+            "kits": self.kits.iter().map(|k| &k.name).collect::<Vec<_>>(),
+            "queued": self.queue.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kit() {
+        let kit = Kit::parse(
+            "toml".to_string(),
+            r#"
+            [[items]]
+            id = 0x40000b54
+            qty = 10
+
+            [[items]]
+            id = 9000000
+            upgrade = 25
+            affinity = "Blood"
+            "#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(kit.items, vec![(0x40000b54, 10), (9001125, 1)]);
+
+        let kit = Kit::parse(
+            "json".to_string(),
+            r#"{ "items": [{ "id": 1073744724, "qty": 5 }, { "id": 1000000, "upgrade": 3 }] }"#,
+            true,
+        )
+        .unwrap();
+        assert_eq!(kit.items, vec![(1073744724, 5), (1000003, 1)]);
+
+        assert!(Kit::parse("bad".to_string(), "[[items]]\nid = 1\nupgrade = 30", false).is_err());
+        assert!(Kit::parse("bad".to_string(), "{ \"items\": [{}] }", true).is_err());
+    }
+}
@@ -1,6 +1,7 @@
-use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::ffi::c_void;
 use std::fmt::Display;
+use std::path::PathBuf;
 
 use imgui::sys::{
     igGetCursorPosX, igGetCursorPosY, igGetTreeNodeToLabelSpacing, igGetWindowPos, igIndent,
@@ -12,9 +13,15 @@ use once_cell::sync::Lazy;
 use practice_tool_core::crossbeam_channel::Sender;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use super::string_match;
+use super::{load_file, string_match, write_file};
+use crate::remote::{arg, select, Control};
+use crate::util::get_dll_relative_path;
+
+/// Number of entries kept in the recently spawned list.
+const HISTORY_LEN: usize = 20;
 
 static AFFINITIES: [(u32, &str); 13] = [
     (0, "No affinity"),
@@ -61,6 +68,138 @@ static UPGRADES: [(u32, &str); 26] = [
     (25, "+25"),
 ];
 
+/// Encodes an item id the way the spawner does: the base id plus the upgrade
+/// level and the offset of the named affinity.
+pub(crate) fn encode_item_id(id: u32, upgrade: u32, affinity: Option<&str>) -> Result<u32, String> {
+    let upgrade = UPGRADES
+        .iter()
+        .find(|(u, _)| *u == upgrade)
+        .map(|(u, _)| *u)
+        .ok_or_else(|| format!("Invalid upgrade level {upgrade}"))?;
+
+    let affinity = match affinity {
+        Some(affinity) => AFFINITIES
+            .iter()
+            .find(|(_, label)| label.eq_ignore_ascii_case(affinity))
+            .map(|(a, _)| *a)
+            .ok_or_else(|| format!("Invalid affinity \"{affinity}\""))?,
+        None => 0,
+    };
+
+    Ok(id + upgrade + affinity)
+}
+
+/// An item id as the item spawner encodes it: the base id plus the upgrade
+/// level and the affinity offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "ItemSpec")]
+pub(crate) struct ItemId(pub(crate) u32);
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ItemSpec {
+    Id(u32),
+    Item {
+        id: u32,
+        #[serde(default)]
+        upgrade: u32,
+        affinity: Option<String>,
+    },
+}
+
+impl TryFrom<ItemSpec> for ItemId {
+    type Error = String;
+
+    fn try_from(spec: ItemSpec) -> Result<Self, Self::Error> {
+        match spec {
+            ItemSpec::Id(id) => Ok(ItemId(id)),
+            ItemSpec::Item { id, upgrade, affinity } => {
+                encode_item_id(id, upgrade, affinity.as_deref()).map(ItemId)
+            },
+        }
+    }
+}
+
+/// Parses a raw item id: either a full id in decimal or `0x` hex, or a
+/// category prefix followed by the param row id, like `goods:2919`.
+pub(crate) fn parse_item_id(s: &str) -> Result<u32, String> {
+    let s = s.trim();
+
+    let (category, number) = match s.split_once(':') {
+        Some((prefix, number)) => {
+            let category = match prefix.trim().to_lowercase().as_str() {
+                "weapon" | "w" => ItemCategory::Weapon,
+                "armor" | "protector" | "a" => ItemCategory::Protector,
+                "talisman" | "accessory" | "t" => ItemCategory::Accessory,
+                "goods" | "g" => ItemCategory::Goods,
+                "aow" | "gem" => ItemCategory::Gem,
+                prefix => return Err(format!("Unknown item category \"{prefix}\"")),
+            };
+            (Some(category), number.trim())
+        },
+        None => (None, s),
+    };
+
+    let id = match number.strip_prefix("0x").or_else(|| number.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => number.parse(),
+    }
+    .map_err(|e| format!("Invalid item id \"{number}\": {e}"))?;
+
+    match category {
+        Some(_) if id > 0x0fff_ffff => Err(format!("Row id {id} out of range")),
+        Some(category) => Ok(category.item_id(id)),
+        None => ItemCategory::from_item_id(id)
+            .map(|_| id)
+            .ok_or_else(|| format!("Invalid item category in {id:08x}")),
+    }
+}
+
+/// Refuses quantities that the game would silently drop: goods above their
+/// `max_num`, and goods that don't exist. Goods can't be checked, and are
+/// refused too, until the params are loaded.
+pub(crate) fn validate_quantity(item_id: u32, qty: u32) -> Result<(), String> {
+    match ItemCategory::from_item_id(item_id) {
+        Some((ItemCategory::Goods, row_id)) => match goods_max_num(row_id)? {
+            Some(max) if qty > max => Err(format!("Can't spawn {qty}, at most {max} can be held")),
+            Some(_) => Ok(()),
+            None => Err(format!("Goods {row_id} don't exist")),
+        },
+        Some(_) => Ok(()),
+        None => Err(format!("Invalid item id {item_id:08x}")),
+    }
+}
+
+pub(crate) fn item_label(item_id: u32) -> String {
+    item_name(item_id).unwrap_or_else(|| format!("{item_id:08x}"))
+}
+
+fn default_qty() -> u32 {
+    1
+}
+
+/// A favourite item, configured in the `item_spawner` command.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Favourite {
+    item: ItemId,
+    #[serde(default = "default_qty")]
+    qty: u32,
+    hotkey: Option<Key>,
+    name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct HistoryEntry {
+    id: u32,
+    qty: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    items: VecDeque<HistoryEntry>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum ItemIDNode {
@@ -172,10 +311,18 @@ pub(crate) struct ItemSpawner<'a> {
     item_id: u32,
     upgrade: usize,
     affinity: usize,
+    weapon_info: Option<(u32, Option<WeaponInfo>)>,
+    gems: Option<Vec<GemInfo>>,
+    gem: Option<usize>,
 
     filter_string: String,
+    raw_id: String,
     logs: Vec<String>,
     item_id_tree: Vec<ItemIDNodeRef<'a>>,
+
+    favourites: Vec<(Favourite, String)>,
+    history_path: Option<PathBuf>,
+    history: VecDeque<HistoryEntry>,
 }
 
 impl ItemSpawner<'_> {
@@ -185,7 +332,24 @@ impl ItemSpawner<'_> {
         sentinel: Bitflag<u8>,
         hotkey_load: Option<Key>,
         hotkey_close: Key,
+        favourites: Vec<Favourite>,
     ) -> Self {
+        let favourites = favourites
+            .into_iter()
+            .map(|mut f| {
+                let name = f.name.get_or_insert_with(|| item_label(f.item.0)).clone();
+                let label = match &f.hotkey {
+                    Some(k) => format!("{name} x{} ({k})", f.qty),
+                    None => format!("{name} x{}", f.qty),
+                };
+                (f, label)
+            })
+            .collect();
+
+        let history_path = get_dll_relative_path("spawn_history.toml");
+        let mut logs = Vec::new();
+        let history = load_file::<HistoryFile>(history_path.as_deref(), &mut logs).items;
+
         let label_load = hotkey_load
             .map(|k| format!("Spawn item ({k})"))
             .unwrap_or_else(|| "Spawn item".to_string());
@@ -202,36 +366,189 @@ impl ItemSpawner<'_> {
             item_id: 0x40000000 + 2919,
             upgrade: 0,
             affinity: 0,
+            weapon_info: None,
+            gems: None,
+            gem: None,
             filter_string: String::new(),
-            logs: Vec::new(),
+            raw_id: String::new(),
+            logs,
             item_id_tree: ITEM_ID_TREE.iter().map(ItemIDNodeRef::from).collect(),
+            favourites,
+            history_path,
+            history,
         }
     }
 
-    fn spawn(&mut self) {
-        if self.sentinel.get().is_none() {
-            self.write_log("Not spawning item when not in game".into());
-            return;
+    /// The `WeaponInfo` of the selected item, read again when the selection
+    /// changes. `None` for items that aren't weapons or aren't in the params.
+    fn weapon_info(&mut self) -> Option<&WeaponInfo> {
+        let item_id = self.item_id;
+        if self.weapon_info.as_ref().map(|(id, _)| *id) != Some(item_id) {
+            let info = match ItemCategory::from_item_id(item_id) {
+                Some((ItemCategory::Weapon, row_id)) => WeaponInfo::from_params(row_id),
+                _ => None,
+            };
+            self.weapon_info = Some((item_id, info));
+            self.gem = None;
+        }
+
+        self.weapon_info.as_ref().and_then(|(_, info)| info.as_ref())
+    }
+
+    fn is_weapon(&self) -> bool {
+        matches!(ItemCategory::from_item_id(self.item_id), Some((ItemCategory::Weapon, _)))
+    }
+
+    fn upgrade_valid(&mut self, upgrade: u32) -> bool {
+        match self.weapon_info() {
+            Some(info) => upgrade <= info.max_upgrade,
+            None => upgrade == 0 || self.is_weapon(),
         }
+    }
+
+    fn affinity_valid(&mut self, affinity: u32) -> bool {
+        match self.weapon_info() {
+            Some(info) => info.affinities.contains(&affinity),
+            None => affinity == 0 || self.is_weapon(),
+        }
+    }
+
+    /// Whether the selected upgrade and affinity can be spawned together on
+    /// the selected item.
+    fn selection_valid(&mut self) -> bool {
+        let (upgrade, affinity) = (UPGRADES[self.upgrade].0, AFFINITIES[self.affinity].0);
+        match self.weapon_info() {
+            Some(info) => info.allows(upgrade, affinity),
+            None => (upgrade == 0 && affinity == 0) || self.is_weapon(),
+        }
+    }
+
+    /// Ashes of War that can go on the selected weapon with the selected
+    /// affinity, as indices into `gems`.
+    fn compatible_gems(&mut self) -> Vec<usize> {
+        let affinity = AFFINITIES[self.affinity].0;
+        let Some(info) = self.weapon_info().filter(|info| info.gem_mountable).cloned() else {
+            return Vec::new();
+        };
+
+        self.gems
+            .get_or_insert_with(GemInfo::all)
+            .iter()
+            .enumerate()
+            .filter(|(_, gem)| gem.can_mount(info.wep_type) && gem.allows_affinity(affinity))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
 
+    fn spawn(&mut self) -> bool {
         let upgrade = UPGRADES[self.upgrade].0;
         let affinity = AFFINITIES[self.affinity].0;
 
+        if !self.selection_valid() {
+            self.write_log(format!(
+                "Not spawning {}: {} {} isn't valid for it",
+                item_label(self.item_id),
+                AFFINITIES[self.affinity].1,
+                UPGRADES[self.upgrade].1
+            ));
+            return false;
+        }
+
+        self.spawn_item(self.item_id + upgrade + affinity, self.qty)
+    }
+
+    /// Spawns the selected Ash of War, picked among those that fit the
+    /// selected weapon and affinity.
+    fn spawn_gem(&mut self) {
+        let Some(gem) = self.gem.and_then(|idx| self.gems.as_ref()?.get(idx)).cloned() else {
+            self.write_log("No Ash of War selected".into());
+            return;
+        };
+
+        if !self.compatible_gems().iter().any(|&idx| Some(idx) == self.gem) {
+            self.write_log(format!("{} can't go on {}", gem.name, item_label(self.item_id)));
+            return;
+        }
+
+        self.spawn_item(gem.item_id(), 1);
+    }
+
+    fn spawn_item(&mut self, item_id: u32, qty: u32) -> bool {
+        if self.sentinel.get().is_none() {
+            self.write_log("Not spawning item when not in game".into());
+            return false;
+        }
+
+        if let Err(e) = validate_quantity(item_id, qty) {
+            self.write_log(format!("Not spawning {}: {e}", item_label(item_id)));
+            return false;
+        }
+
         let i = ItemSpawnInstance {
             spawn_item_func_ptr: self.func_ptr as _,
             map_item_man: self.map_item_man as _,
-            qty: self.qty,
-            item_id: self.item_id + upgrade + affinity,
+            qty,
+            item_id,
         };
 
-        self.write_log(format!(
-            "Spawning {} #{} {} {}",
-            i.qty, self.item_id, UPGRADES[self.upgrade].1, AFFINITIES[self.affinity].1,
-        ));
+        self.write_log(format!("Spawning {qty} {} ({item_id:08x})", item_label(item_id)));
 
         unsafe {
             i.spawn();
         }
+
+        self.push_history(HistoryEntry { id: item_id, qty });
+        true
+    }
+
+    fn push_history(&mut self, entry: HistoryEntry) {
+        self.history.retain(|e| *e != entry);
+        self.history.push_front(entry);
+        self.history.truncate(HISTORY_LEN);
+
+        let file = HistoryFile { items: self.history.clone() };
+        write_file(self.history_path.as_deref(), &file, &mut self.logs);
+    }
+
+    fn use_raw_id(&mut self) {
+        match parse_item_id(&self.raw_id) {
+            Ok(item_id) => {
+                self.item_id = item_id;
+                self.upgrade = 0;
+                self.affinity = 0;
+            },
+            Err(e) => self.write_log(e),
+        }
+    }
+
+    fn render_lists(&mut self, ui: &imgui::Ui, button_height: f32) {
+        if !self.favourites.is_empty() {
+            ui.text("Favourites");
+            let mut spawn = None;
+            for (idx, (favourite, label)) in self.favourites.iter().enumerate() {
+                if ui.button_with_size(format!("{label}##fav-{idx}"), [400., button_height]) {
+                    spawn = Some((favourite.item.0, favourite.qty));
+                }
+            }
+            if let Some((item_id, qty)) = spawn {
+                self.spawn_item(item_id, qty);
+            }
+        }
+
+        if !self.history.is_empty() {
+            ui.text("Recently spawned");
+            ui.child_window("##item-spawn-history").size([400., 100.]).build(|| {
+                for (idx, entry) in self.history.iter().enumerate() {
+                    let label = format!("{} x{}##hist-{idx}", item_label(entry.id), entry.qty);
+                    if ui.selectable(label) {
+                        self.item_id = entry.id;
+                        self.qty = entry.qty;
+                        self.upgrade = 0;
+                        self.affinity = 0;
+                    }
+                }
+            });
+        }
     }
 
     fn write_log(&mut self, log: String) {
@@ -292,23 +609,89 @@ impl Widget for ItemSpawner<'_> {
             });
 
             ui.set_next_item_width(195.);
-            ui.combo("##item-spawn-affinity", &mut self.affinity, &AFFINITIES, |(_, label)| {
-                Cow::Borrowed(label)
-            });
+            if let Some(_tok) = ui.begin_combo("##item-spawn-affinity", AFFINITIES[self.affinity].1)
+            {
+                for (idx, (affinity, label)) in AFFINITIES.iter().enumerate() {
+                    let valid = self.affinity_valid(*affinity);
+                    if ui
+                        .selectable_config(label)
+                        .selected(idx == self.affinity)
+                        .disabled(!valid)
+                        .build()
+                    {
+                        self.affinity = idx;
+                    }
+                }
+            }
 
             ui.same_line();
             ui.set_next_item_width(195.);
-            ui.combo("##item-spawn-upgrade", &mut self.upgrade, &UPGRADES, |(_, label)| {
-                Cow::Borrowed(label)
-            });
+            if let Some(_tok) = ui.begin_combo("##item-spawn-upgrade", UPGRADES[self.upgrade].1) {
+                for (idx, (upgrade, label)) in UPGRADES.iter().enumerate() {
+                    let valid = self.upgrade_valid(*upgrade);
+                    if ui
+                        .selectable_config(label)
+                        .selected(idx == self.upgrade)
+                        .disabled(!valid)
+                        .build()
+                    {
+                        self.upgrade = idx;
+                    }
+                }
+            }
+
+            let gems = self.compatible_gems();
+            if !gems.is_empty() {
+                let preview = self
+                    .gem
+                    .filter(|idx| gems.contains(idx))
+                    .and_then(|idx| self.gems.as_ref()?.get(idx))
+                    .map(|gem| gem.name.clone())
+                    .unwrap_or_else(|| "Ash of War...".to_string());
+
+                ui.set_next_item_width(195.);
+                if let Some(_tok) = ui.begin_combo("##item-spawn-gem", preview) {
+                    for idx in gems {
+                        let Some(gem) = self.gems.as_ref().and_then(|gems| gems.get(idx)) else {
+                            continue;
+                        };
+                        let label = format!("{}##gem-{idx}", gem.name);
+                        if ui.selectable_config(label).selected(self.gem == Some(idx)).build() {
+                            self.gem = Some(idx);
+                        }
+                    }
+                }
+                ui.same_line();
+                if ui.button_with_size("Spawn Ash of War", [195., button_height]) {
+                    self.spawn_gem();
+                }
+            }
+
+            {
+                let _tok = ui.push_item_width(295.);
+                InputText::new(ui, "##item-spawn-raw-id", &mut self.raw_id)
+                    .hint("Item ID (0x40000b67, goods:2919...)")
+                    .build();
+            }
+            ui.same_line();
+            if ui.button_with_size("Use ID", [100., button_height]) {
+                self.use_raw_id();
+            }
+            ui.text(format!("Selected: {} ({:08x})", item_label(self.item_id), self.item_id));
 
             ui.slider_config("Qty", 1, 99).build(&mut self.qty);
-            if ui.button_with_size(&self.label_load, [400., button_height]) {
-                self.spawn();
+            {
+                let _tok = ui.begin_disabled(!self.selection_valid());
+                if ui.button_with_size(&self.label_load, [400., button_height]) {
+                    self.spawn();
+                }
             }
 
+            self.render_lists(ui, button_height);
+
             if ui.button_with_size("Clear", [400., button_height]) {
                 self.filter_string.clear();
+                self.raw_id.clear();
                 self.qty = 1;
                 self.item_id = 0x40000000 + 2919;
                 self.upgrade = 0;
@@ -326,9 +709,23 @@ impl Widget for ItemSpawner<'_> {
     }
 
     fn interact(&mut self, ui: &imgui::Ui) {
+        if ui.is_any_item_active() {
+            return;
+        }
+
         if self.hotkey_load.map(|k| k.is_pressed(ui)).unwrap_or(false) {
             self.spawn();
         }
+
+        let pressed: Vec<_> = self
+            .favourites
+            .iter()
+            .filter(|(f, _)| f.hotkey.map(|k| k.is_pressed(ui)).unwrap_or(false))
+            .map(|(f, _)| (f.item.0, f.qty))
+            .collect();
+        for (item_id, qty) in pressed {
+            self.spawn_item(item_id, qty);
+        }
     }
 
     fn log(&mut self, tx: Sender<String>) {
@@ -338,12 +735,52 @@ impl Widget for ItemSpawner<'_> {
     }
 }
 
+/// `spawn` spawns the selected item, or the one given as an item id like
+/// the raw id field takes, `{ "item": "goods:2919", "quantity": 5 }`.
+/// `favourite` takes the name of a favourite: `{ "name": "Rowa Raisin" }`.
+impl Control for ItemSpawner<'_> {
+    fn label(&self) -> String {
+        self.label_load.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["spawn", "favourite"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        let spawned = match (action, arg::<Option<&str>>(args, "item")?) {
+            ("favourite", _) => {
+                let idx = select(&self.favourites, |(f, _)| f.name.as_deref().unwrap_or(""), args)?;
+                let (item_id, qty) = (self.favourites[idx].0.item.0, self.favourites[idx].0.qty);
+                self.spawn_item(item_id, qty)
+            },
+            (_, Some(item)) => {
+                let qty = arg::<Option<u32>>(args, "quantity")?.unwrap_or(1);
+                self.spawn_item(parse_item_id(item)?, qty)
+            },
+            (_, None) => self.spawn(),
+        };
+
+        // The reason the item wasn't spawned is the last thing logged.
+        if spawned {
+            Ok(())
+        } else {
+            Err(self.logs.last().cloned().unwrap_or_default())
+        }
+    }
+
+    fn state(&mut self) -> Value {
+        let favourites = self.favourites.iter().map(|(f, _)| f.name.as_deref()).collect::<Vec<_>>();
+        json!({ "selected": item_label(self.item_id), "favourites": favourites })
+    }
+}
+
 #[derive(Debug)]
-struct ItemSpawnInstance {
-    spawn_item_func_ptr: u64,
-    map_item_man: u64,
-    qty: u32,
-    item_id: u32,
+pub(crate) struct ItemSpawnInstance {
+    pub(crate) spawn_item_func_ptr: u64,
+    pub(crate) map_item_man: u64,
+    pub(crate) qty: u32,
+    pub(crate) item_id: u32,
 }
 
 impl Display for ItemSpawnInstance {
@@ -353,7 +790,7 @@ impl Display for ItemSpawnInstance {
 }
 
 impl ItemSpawnInstance {
-    unsafe fn spawn(&self) {
+    pub(crate) unsafe fn spawn(&self) {
         #[repr(C)]
         struct SpawnRequest {
             one: u32,
@@ -373,3 +810,24 @@ impl ItemSpawnInstance {
         spawn_fn_ptr(*pp_map_item_man, &mut spawn_request as *mut _, &mut dur as *mut _, 0u32);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_item_id() {
+        assert_eq!(parse_item_id("0x40000b67"), Ok(0x40000b67));
+        assert_eq!(parse_item_id("1000000"), Ok(1000000));
+        assert_eq!(parse_item_id("goods:2919"), Ok(0x40000000 + 2919));
+        assert_eq!(parse_item_id(" Talisman : 0x3e8 "), Ok(0x200003e8));
+        assert_eq!(parse_item_id("aow:10000"), Ok(0x80000000 + 10000));
+        assert_eq!(parse_item_id("w:1000000"), Ok(1000000));
+
+        assert!(parse_item_id("").is_err());
+        assert!(parse_item_id("0x30000000").is_err());
+        assert!(parse_item_id("spell:100").is_err());
+        assert!(parse_item_id("goods:0x40000000").is_err());
+        assert!(parse_item_id("goods:abc").is_err());
+    }
+}
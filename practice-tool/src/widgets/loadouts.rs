@@ -0,0 +1,427 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use imgui::InputText;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::item_spawn::{ItemId, ItemSpawnInstance};
+use super::{begin_popup, close_button, load_file, popup_anchor, write_file};
+use crate::remote::{arg, select, Control};
+use crate::util::get_dll_relative_path;
+
+const POPUP_TAG: &str = "##loadouts";
+
+/// Quantity spawned for missing arrows and bolts.
+const AMMO_QUANTITY: u32 = 99;
+
+/// A named set of equipped items. Slots that aren't listed are left as they
+/// are; listed slot groups are filled in order and the rest of the group is
+/// emptied.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct Loadout {
+    name: String,
+    right_hand: Option<Vec<ItemId>>,
+    left_hand: Option<Vec<ItemId>>,
+    arrows: Option<Vec<ItemId>>,
+    bolts: Option<Vec<ItemId>>,
+    head: Option<ItemId>,
+    chest: Option<ItemId>,
+    arms: Option<ItemId>,
+    legs: Option<ItemId>,
+    talismans: Option<Vec<ItemId>>,
+    spells: Option<Vec<ItemId>>,
+}
+
+/// Loadouts captured in game, persisted to `loadouts.toml` next to the DLL.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct LoadoutsFile {
+    #[serde(default)]
+    loadouts: Vec<Loadout>,
+}
+
+/// Fills a slot group from a list of items, padding with `empty`.
+fn fill<const N: usize>(items: &[ItemId], empty: u32) -> [u32; N] {
+    std::array::from_fn(|i| items.get(i).map(|&ItemId(id)| row_id(id)).unwrap_or(empty))
+}
+
+fn row_id(item_id: u32) -> u32 {
+    ItemCategory::from_item_id(item_id).map(|(_, row_id)| row_id).unwrap_or(item_id)
+}
+
+/// Whether an id stands for an empty slot rather than an actual item.
+fn is_empty_slot(item_id: u32) -> bool {
+    let row_id = row_id(item_id);
+    item_id == EMPTY_SLOT || row_id == UNARMED || BARE_ARMOR.contains(&row_id)
+}
+
+impl Loadout {
+    fn capture(name: String, items: &EquippedItems, spells: &EquippedSpells) -> Self {
+        let ids = |ids: &[u32], category: ItemCategory| {
+            ids.iter()
+                .filter(|&&id| id != EMPTY_SLOT)
+                .map(|&id| ItemId(category.item_id(id)))
+                .collect()
+        };
+        let [head, chest, arms, legs] =
+            items.armor.map(|id| Some(ItemId(ItemCategory::Protector.item_id(id))));
+
+        Loadout {
+            name,
+            right_hand: Some(ids(&items.right_hand(), ItemCategory::Weapon)),
+            left_hand: Some(ids(&items.left_hand(), ItemCategory::Weapon)),
+            arrows: Some(ids(&items.arrows(), ItemCategory::Weapon)),
+            bolts: Some(ids(&items.bolts(), ItemCategory::Weapon)),
+            head,
+            chest,
+            arms,
+            legs,
+            talismans: Some(ids(&items.talismans, ItemCategory::Accessory)),
+            spells: Some(ids(&spells.map(|s| s.spell_id), ItemCategory::Goods)),
+        }
+    }
+
+    /// Items to spawn if missing from the inventory, with their quantity.
+    fn items(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let lists = [
+            (&self.right_hand, 1),
+            (&self.left_hand, 1),
+            (&self.arrows, AMMO_QUANTITY),
+            (&self.bolts, AMMO_QUANTITY),
+            (&self.talismans, 1),
+            (&self.spells, 1),
+        ];
+        let armor = [&self.head, &self.chest, &self.arms, &self.legs];
+
+        lists
+            .into_iter()
+            .flat_map(|(list, qty)| list.iter().flatten().map(move |&ItemId(id)| (id, qty)))
+            .chain(armor.into_iter().flatten().map(|&ItemId(id)| (id, 1)))
+            .filter(|&(id, _)| !is_empty_slot(id))
+    }
+
+    fn apply_to(&self, items: &mut EquippedItems, spells: &mut EquippedSpells) {
+        if let Some(right_hand) = &self.right_hand {
+            items.set_right_hand(fill(right_hand, UNARMED));
+        }
+        if let Some(left_hand) = &self.left_hand {
+            items.set_left_hand(fill(left_hand, UNARMED));
+        }
+        if let Some(arrows) = &self.arrows {
+            items.set_arrows(fill(arrows, EMPTY_SLOT));
+        }
+        if let Some(bolts) = &self.bolts {
+            items.set_bolts(fill(bolts, EMPTY_SLOT));
+        }
+        for (i, armor) in [&self.head, &self.chest, &self.arms, &self.legs].into_iter().enumerate()
+        {
+            if let Some(ItemId(id)) = armor {
+                items.armor[i] = row_id(*id);
+            }
+        }
+        if let Some(talismans) = &self.talismans {
+            items.talismans = fill(talismans, EMPTY_SLOT);
+        }
+        if let Some(list) = &self.spells {
+            *spells = fill::<14>(list, EMPTY_SLOT).map(EquippedSpell::new);
+        }
+    }
+}
+
+/// Applies named equipment loadouts, spawning missing items first. Loadouts
+/// come from the config and from `loadouts.toml`, where captured ones are
+/// saved.
+///
+/// Only the item ids in `ChrAsm` are written, not the inventory indices and
+/// gaitem handles that go with them, so the game may keep showing the old
+/// equipment until the character is reloaded, e.g. by quitting out.
+pub(crate) struct Loadouts {
+    label: String,
+    label_close: String,
+    hotkey_close: Key,
+
+    equipped_items: PointerChain<EquippedItems>,
+    equipped_spells: PointerChain<EquippedSpells>,
+    inventory: Inventory,
+    func_ptr: usize,
+    map_item_man: usize,
+    sentinel: Bitflag<u8>,
+
+    path: Option<PathBuf>,
+    loadouts: Vec<Loadout>,
+    // Loadouts before this index come from the config and aren't saved.
+    config_loadouts: usize,
+    selected: Option<usize>,
+    new_name: String,
+
+    logs: Vec<String>,
+}
+
+impl Loadouts {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        loadouts: Vec<Loadout>,
+        equipped_items: PointerChain<EquippedItems>,
+        equipped_spells: PointerChain<EquippedSpells>,
+        inventory: Inventory,
+        func_ptr: usize,
+        map_item_man: usize,
+        sentinel: Bitflag<u8>,
+        hotkey_close: Key,
+    ) -> Self {
+        let path = get_dll_relative_path("loadouts.toml");
+
+        let mut logs = Vec::new();
+        let config_loadouts = loadouts.len();
+        let mut loadouts = loadouts;
+        loadouts.extend(load_file::<LoadoutsFile>(path.as_deref(), &mut logs).loadouts);
+
+        Loadouts {
+            label: "Loadouts".to_string(),
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            equipped_items,
+            equipped_spells,
+            inventory,
+            func_ptr,
+            map_item_man,
+            sentinel,
+            path,
+            loadouts,
+            config_loadouts,
+            selected: None,
+            new_name: String::new(),
+            logs,
+        }
+    }
+
+    fn write_file(&mut self) {
+        let file = LoadoutsFile { loadouts: self.loadouts[self.config_loadouts..].to_vec() };
+        write_file(self.path.as_deref(), &file, &mut self.logs);
+    }
+
+    fn apply_selected(&mut self) {
+        let Some(loadout) = self.selected.and_then(|idx| self.loadouts.get(idx)) else {
+            return;
+        };
+
+        if self.sentinel.get().is_none() {
+            self.logs.push("Not applying loadout when not in game".to_string());
+            return;
+        }
+
+        let (Some(mut items), Some(mut spells), Some(inventory)) =
+            (self.equipped_items.read(), self.equipped_spells.read(), self.inventory.items())
+        else {
+            self.logs.push("Couldn't read equipment".to_string());
+            return;
+        };
+
+        let held: HashSet<u32> = inventory.iter().map(|(_, item)| item.item_id).collect();
+        let mut spawned = 0;
+        for (item_id, qty) in loadout.items() {
+            if !held.contains(&item_id) {
+                let i = ItemSpawnInstance {
+                    spawn_item_func_ptr: self.func_ptr as _,
+                    map_item_man: self.map_item_man as _,
+                    qty,
+                    item_id,
+                };
+                unsafe { i.spawn() };
+                spawned += 1;
+            }
+        }
+
+        loadout.apply_to(&mut items, &mut spells);
+        self.equipped_items.write(items);
+        self.equipped_spells.write(spells);
+
+        self.logs.push(format!(
+            "Applied \"{}\" ({spawned} items spawned); reload to update the equipment menu",
+            loadout.name
+        ));
+    }
+
+    fn capture_current(&mut self) {
+        let (Some(items), Some(spells)) = (self.equipped_items.read(), self.equipped_spells.read())
+        else {
+            self.logs.push("Couldn't read equipment".to_string());
+            return;
+        };
+
+        let name = match self.new_name.trim() {
+            "" => format!("Loadout {}", self.loadouts.len() + 1),
+            name => name.to_string(),
+        };
+
+        self.logs.push(format!("Captured \"{name}\""));
+        self.loadouts.push(Loadout::capture(name, &items, &spells));
+        self.selected = Some(self.loadouts.len() - 1);
+        self.new_name.clear();
+        self.write_file();
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(idx) = self.selected.filter(|&idx| idx < self.loadouts.len()) else {
+            return;
+        };
+
+        if idx < self.config_loadouts {
+            self.logs.push("Loadouts from the config can only be deleted there".to_string());
+            return;
+        }
+
+        let loadout = self.loadouts.remove(idx);
+        self.selected = None;
+        self.logs.push(format!("Deleted \"{}\"", loadout.name));
+        self.write_file();
+    }
+}
+
+impl Widget for Loadouts {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            let _tok = ui.push_item_width(-1.);
+
+            ui.child_window("##loadouts-list").size([400., 200.]).build(|| {
+                for (idx, loadout) in self.loadouts.iter().enumerate() {
+                    let label = format!("{}##{idx}", loadout.name);
+                    if ui.selectable_config(&label).selected(self.selected == Some(idx)).build() {
+                        self.selected = Some(idx);
+                    }
+                }
+            });
+
+            if ui.button_with_size("Apply", [400., button_height]) {
+                self.apply_selected();
+            }
+
+            InputText::new(ui, "##loadouts-name", &mut self.new_name).hint("Name...").build();
+
+            if ui.button_with_size("Capture current loadout", [400., button_height]) {
+                self.capture_current();
+            }
+
+            if ui.button_with_size("Delete", [400., button_height]) {
+                self.delete_selected();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// `capture` takes an optional name, `apply` and `delete` the name of a
+/// loadout: `{ "name": "Bleed" }`.
+impl Control for Loadouts {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["apply", "capture", "delete"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "capture" {
+            self.new_name = arg::<Option<String>>(args, "name")?.unwrap_or_default();
+            self.capture_current();
+            return Ok(());
+        }
+
+        self.selected = Some(select(&self.loadouts, |l| &l.name, args)?);
+        match action {
+            "apply" => self.apply_selected(),
+            _ => self.delete_selected(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({ "loadouts": self.loadouts.iter().map(|l| &l.name).collect::<Vec<_>>() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Wrapper {
+        loadout: Loadout,
+    }
+
+    fn parse(s: &str) -> Result<Loadout, toml::de::Error> {
+        toml::from_str::<Wrapper>(&format!("loadout = {s}")).map(|w| w.loadout)
+    }
+
+    #[test]
+    fn test_item_spec() {
+        let loadout = parse(
+            r#"{ name = "Bleed", right_hand = [9000000, { id = 9000000, upgrade = 25, affinity = "blood" }], talismans = [0x200003e8] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(loadout.right_hand, Some(vec![ItemId(9000000), ItemId(9001125)]));
+        assert_eq!(loadout.talismans, Some(vec![ItemId(0x200003e8)]));
+        assert_eq!(loadout.left_hand, None);
+
+        assert!(parse(r#"{ name = "x", right_hand = [{ id = 1, upgrade = 26 }] }"#).is_err());
+        assert!(parse(r#"{ name = "x", right_hand = [{ id = 1, affinity = "Nope" }] }"#).is_err());
+    }
+
+    #[test]
+    fn test_apply_and_capture() {
+        let loadout = parse(
+            r#"{ name = "a", right_hand = [9000000], talismans = [0x200003e8], head = 0x10000bb8, spells = [0x40000fa0] }"#,
+        )
+        .unwrap();
+
+        let mut items = EquippedItems::default();
+        items.talismans = [1, 2, 3, 4];
+        let mut spells = EquippedSpells::default();
+        loadout.apply_to(&mut items, &mut spells);
+
+        assert_eq!(items.right_hand(), [9000000, UNARMED, UNARMED]);
+        assert_eq!(items.left_hand(), [0, 0, 0]);
+        assert_eq!(items.talismans, [1000, EMPTY_SLOT, EMPTY_SLOT, EMPTY_SLOT]);
+        assert_eq!(items.armor, [3000, 0, 0, 0]);
+        assert_eq!(spells[0].spell_id, 4000);
+        assert_eq!(spells[1].spell_id, EMPTY_SLOT);
+
+        let captured = Loadout::capture("b".to_string(), &items, &spells);
+        assert_eq!(captured.talismans, Some(vec![ItemId(0x200003e8)]));
+        assert_eq!(captured.spells, Some(vec![ItemId(0x40000fa0)]));
+        assert_eq!(captured.head, Some(ItemId(0x10000bb8)));
+
+        let file = LoadoutsFile { loadouts: vec![captured.clone()] };
+        let reparsed = toml::from_str::<LoadoutsFile>(&toml::to_string(&file).unwrap()).unwrap();
+        let [reparsed] = reparsed.loadouts.try_into().unwrap();
+        assert_eq!(reparsed.right_hand, captured.right_hand);
+        assert_eq!(reparsed.spells, captured.spells);
+        assert_eq!(reparsed.head, captured.head);
+
+        let spawned: Vec<_> = loadout.items().collect();
+        assert_eq!(spawned, vec![(9000000, 1), (0x200003e8, 1), (0x40000fa0, 1), (0x10000bb8, 1)]);
+    }
+}
@@ -1,17 +1,46 @@
+use std::path::Path;
+
+use hudhook::tracing::error;
+use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, igSetNextWindowPos, ImVec2};
+use imgui::{Condition, PopupToken, WindowFlags};
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, BUTTON_HEIGHT};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::util::{load_toml, write_toml};
+
 pub(crate) mod action_freeze;
+pub(crate) mod auto_reset;
+pub(crate) mod boss_reset;
+pub(crate) mod build_presets;
 pub(crate) mod character_stats;
+pub(crate) mod checkpoints;
 pub(crate) mod cycle_speed;
 pub(crate) mod deathcam;
+pub(crate) mod event_flags;
 pub(crate) mod flag;
+pub(crate) mod flasks_blessings;
 pub(crate) mod group;
+pub(crate) mod inventory;
+pub(crate) mod item_kits;
 pub(crate) mod item_spawn;
+pub(crate) mod loadouts;
 pub(crate) mod multiflag;
+pub(crate) mod noclip;
 pub(crate) mod nudge_pos;
+pub(crate) mod player_vitals;
 pub(crate) mod position;
+pub(crate) mod position_library;
 pub(crate) mod quitout;
 pub(crate) mod runes;
 pub(crate) mod savefile_manager;
+pub(crate) mod segment_timer;
+pub(crate) mod split_timer;
+pub(crate) mod stats;
 pub(crate) mod target;
+pub(crate) mod teleport;
+pub(crate) mod trigger;
 pub(crate) mod warp;
 
 pub(crate) fn string_match(needle: &str, haystack: &str) -> bool {
@@ -28,3 +57,76 @@ pub(crate) fn string_match(needle: &str, haystack: &str) -> bool {
     }
     true
 }
+
+/// Screen position of the cursor. Take it before drawing the button that opens
+/// a popup, and pass it to [`begin_popup`].
+pub(crate) fn popup_anchor() -> [f32; 2] {
+    unsafe {
+        let mut wnd_pos = ImVec2::default();
+        igGetWindowPos(&mut wnd_pos);
+        [igGetCursorPosX() + wnd_pos.x, igGetCursorPosY() + wnd_pos.y]
+    }
+}
+
+/// Begins a modal popup next to the button drawn at `anchor`.
+pub(crate) fn begin_popup<'ui>(
+    ui: &'ui imgui::Ui,
+    tag: &str,
+    [x, y]: [f32; 2],
+) -> Option<PopupToken<'ui>> {
+    let scale = scaling_factor(ui);
+
+    unsafe {
+        igSetNextWindowPos(
+            ImVec2::new(x + 200. * scale, y),
+            Condition::Always as i8 as _,
+            ImVec2::new(0., 0.),
+        )
+    };
+
+    ui.modal_popup_config(tag)
+        .flags(
+            WindowFlags::NO_TITLE_BAR
+                | WindowFlags::NO_RESIZE
+                | WindowFlags::NO_MOVE
+                | WindowFlags::NO_SCROLLBAR
+                | WindowFlags::ALWAYS_AUTO_RESIZE,
+        )
+        .begin_popup()
+}
+
+/// Close button of a popup, also pressed by the close hotkey. Returns whether
+/// the popup was closed.
+pub(crate) fn close_button(ui: &imgui::Ui, label: &str, hotkey: Key, width: f32) -> bool {
+    let close = ui.button_with_size(label, [width, BUTTON_HEIGHT])
+        || (hotkey.is_pressed(ui) && !ui.is_any_item_active());
+
+    if close {
+        ui.close_current_popup();
+    }
+    close
+}
+
+/// Loads a widget's TOML file, falling back to the default value and logging
+/// if it can't be read.
+pub(crate) fn load_file<T: DeserializeOwned + Default>(
+    path: Option<&Path>,
+    logs: &mut Vec<String>,
+) -> T {
+    match path.map(load_toml).transpose() {
+        Ok(value) => value.unwrap_or_default(),
+        Err(e) => {
+            error!("{e}");
+            logs.push(e);
+            T::default()
+        },
+    }
+}
+
+/// Writes a widget's TOML file, logging if it can't be written.
+pub(crate) fn write_file<T: Serialize>(path: Option<&Path>, value: &T, logs: &mut Vec<String>) {
+    if let Some(Err(e)) = path.map(|path| write_toml(path, value)) {
+        error!("{e}");
+        logs.push(e);
+    }
+}
@@ -1,15 +1,17 @@
 use libeldenring::memedit::Bitflag;
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::flag::{Flag, FlagWidget};
+use practice_tool_core::widgets::flag::Flag;
 use practice_tool_core::widgets::Widget;
 
+use crate::remote::{Control, FlagCommand};
+
 #[derive(Debug)]
-struct MultiFlag {
+pub(crate) struct MultiFlag {
     bitflags: Vec<Bitflag<u8>>,
 }
 
 impl MultiFlag {
-    fn new(bitflags: Vec<Bitflag<u8>>) -> Self {
+    pub(crate) fn new(bitflags: Vec<Bitflag<u8>>) -> Self {
         Self { bitflags }
     }
 }
@@ -30,6 +32,6 @@ pub(crate) fn multi_flag(
     label: &str,
     bitflags: Vec<Bitflag<u8>>,
     key: Option<Key>,
-) -> Box<dyn Widget> {
-    Box::new(FlagWidget::new(label, MultiFlag::new(bitflags), key))
+) -> impl Widget + Control {
+    FlagCommand::new(label, MultiFlag::new(bitflags), key)
 }
@@ -0,0 +1,257 @@
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::Widget;
+use serde_json::{json, Value};
+
+use super::nudge_pos::facing_direction;
+use crate::remote::Control;
+
+/// Hotkeys used to move around while noclip is active.
+#[derive(Debug, Clone)]
+pub(crate) struct NoclipKeys {
+    pub(crate) forward: Option<Key>,
+    pub(crate) back: Option<Key>,
+    pub(crate) left: Option<Key>,
+    pub(crate) right: Option<Key>,
+    pub(crate) up: Option<Key>,
+    pub(crate) down: Option<Key>,
+    pub(crate) boost: Option<Key>,
+}
+
+impl Default for NoclipKeys {
+    /// WASD to move, space and left control to go up and down, left shift to
+    /// boost.
+    fn default() -> Self {
+        let key = |name: &str| name.parse().ok();
+        NoclipKeys {
+            forward: key("w"),
+            back: key("s"),
+            left: key("a"),
+            right: key("d"),
+            up: key("space"),
+            down: key("lctrl"),
+            boost: key("lshift"),
+        }
+    }
+}
+
+/// Free-fly movement: gravity and collision are disabled and the character is
+/// moved along the camera axes every frame while the movement keys are held.
+#[derive(Debug)]
+pub(crate) struct Noclip {
+    label: String,
+    hotkey: Option<Key>,
+    keys: NoclipKeys,
+    speed: f32,
+    boost: f32,
+
+    gravity: Bitflag<u8>,
+    collision: Bitflag<u8>,
+    torrent_gravity: Bitflag<u8>,
+    torrent_collision: Bitflag<u8>,
+    chunk_position: Position,
+    torrent_chunk_position: Position,
+    camera_direction: PointerChain<[f32; 3]>,
+
+    // Flag states from before noclip was enabled, restored on exit.
+    saved_flags: Option<[Option<bool>; 4]>,
+}
+
+impl Noclip {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        gravity: Bitflag<u8>,
+        collision: Bitflag<u8>,
+        torrent_gravity: Bitflag<u8>,
+        torrent_collision: Bitflag<u8>,
+        chunk_position: Position,
+        torrent_chunk_position: Position,
+        camera_direction: PointerChain<[f32; 3]>,
+        speed: f32,
+        boost: f32,
+        keys: NoclipKeys,
+        hotkey: Option<Key>,
+    ) -> Self {
+        let label = match &hotkey {
+            Some(k) => format!("Noclip ({k})"),
+            None => "Noclip".to_string(),
+        };
+
+        Noclip {
+            label,
+            hotkey,
+            keys,
+            speed,
+            boost,
+            gravity,
+            collision,
+            torrent_gravity,
+            torrent_collision,
+            chunk_position,
+            torrent_chunk_position,
+            camera_direction,
+            saved_flags: None,
+        }
+    }
+
+    fn flags(&self) -> [&Bitflag<u8>; 4] {
+        [&self.gravity, &self.collision, &self.torrent_gravity, &self.torrent_collision]
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        match (enabled, self.saved_flags.take()) {
+            (true, None) => {
+                self.saved_flags = Some(self.flags().map(|f| f.get()));
+                // These flags disable gravity and collision when set.
+                self.flags().iter().for_each(|f| f.set(true));
+            },
+            (false, Some(saved)) => {
+                for (flag, state) in self.flags().into_iter().zip(saved) {
+                    if let Some(state) = state {
+                        flag.set(state);
+                    }
+                }
+            },
+            (_, saved) => self.saved_flags = saved,
+        }
+    }
+
+    /// Camera forward direction projected on the horizontal plane, falling
+    /// back to the character's facing if the camera can't be read.
+    fn forward(&self) -> Option<[f32; 3]> {
+        self.camera_direction
+            .read()
+            .and_then(|[x, _, z]| horizontal_direction(x, z))
+            .or_else(|| self.chunk_position.angle1.read().map(facing_direction))
+    }
+
+    fn is_down(key: &Option<Key>, ui: &imgui::Ui) -> bool {
+        key.as_ref().map(|k| k.is_down(ui)).unwrap_or(false)
+    }
+
+    fn fly(&self, ui: &imgui::Ui) {
+        let axis = |pos: &Option<Key>, neg: &Option<Key>| {
+            Self::is_down(pos, ui) as i32 as f32 - Self::is_down(neg, ui) as i32 as f32
+        };
+
+        let input = [
+            axis(&self.keys.forward, &self.keys.back),
+            axis(&self.keys.right, &self.keys.left),
+            axis(&self.keys.up, &self.keys.down),
+        ];
+
+        if input == [0.; 3] {
+            return;
+        }
+
+        let Some(forward) = self.forward() else {
+            return;
+        };
+
+        let speed =
+            if Self::is_down(&self.keys.boost, ui) { self.speed * self.boost } else { self.speed };
+        let [dx, dy, dz] = movement_delta(forward, input, speed * ui.io().delta_time);
+
+        for position in [&self.chunk_position, &self.torrent_chunk_position] {
+            if let Some([x, y, z, _, _]) = position.read() {
+                position.x.write(x + dx);
+                position.y.write(y + dy);
+                position.z.write(z + dz);
+            }
+        }
+    }
+}
+
+/// Normalizes the horizontal component of a direction.
+fn horizontal_direction(x: f32, z: f32) -> Option<[f32; 3]> {
+    let len = (x * x + z * z).sqrt();
+    (len > f32::EPSILON).then(|| [x / len, 0., z / len])
+}
+
+/// Displacement for a `[forward, right, up]` input, relative to a horizontal
+/// `forward` direction. Diagonal movement is not faster than straight movement.
+fn movement_delta([fx, _, fz]: [f32; 3], [f, r, u]: [f32; 3], distance: f32) -> [f32; 3] {
+    let [x, y, z] = [fx * f + fz * r, u, fz * f - fx * r];
+    let len = (x * x + y * y + z * z).sqrt();
+    if len <= f32::EPSILON {
+        return [0.; 3];
+    }
+
+    [x / len * distance, y / len * distance, z / len * distance]
+}
+
+impl Widget for Noclip {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let mut enabled = self.saved_flags.is_some();
+        if ui.checkbox(&self.label, &mut enabled) {
+            self.set_enabled(enabled);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if self.hotkey.as_ref().map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.set_enabled(self.saved_flags.is_none());
+        }
+
+        if self.saved_flags.is_some() && !ui.is_any_item_active() {
+            self.fly(ui);
+        }
+    }
+}
+
+impl Control for Noclip {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["toggle", "on", "off"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        let enabled = match action {
+            "on" => true,
+            "off" => false,
+            _ => self.saved_flags.is_none(),
+        };
+        self.set_enabled(enabled);
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({ "enabled": self.saved_flags.is_some() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_horizontal_direction() {
+        assert_close(horizontal_direction(3., 4.).unwrap(), [0.6, 0., 0.8]);
+        assert_close(horizontal_direction(0., -2.).unwrap(), [0., 0., -1.]);
+        assert!(horizontal_direction(0., 0.).is_none());
+    }
+
+    #[test]
+    fn test_movement_delta() {
+        let forward = [0., 0., 1.];
+        assert_close(movement_delta(forward, [1., 0., 0.], 2.), [0., 0., 2.]);
+        assert_close(movement_delta(forward, [-1., 0., 0.], 2.), [0., 0., -2.]);
+        assert_close(movement_delta(forward, [0., 1., 0.], 2.), [2., 0., 0.]);
+        assert_close(movement_delta(forward, [0., 0., 1.], 2.), [0., 2., 0.]);
+        assert_close(movement_delta([1., 0., 0.], [1., 0., 0.], 1.), [1., 0., 0.]);
+
+        // Diagonals cover the same distance as straight lines.
+        let [x, y, z] = movement_delta(forward, [1., 1., 1.], 3.);
+        assert!(((x * x + y * y + z * z).sqrt() - 3.).abs() < 1e-5);
+
+        // Opposite keys cancel out.
+        assert_eq!(movement_delta(forward, [0., 0., 0.], 3.), [0.; 3]);
+    }
+}
@@ -1,10 +1,191 @@
+use std::f32::consts::PI;
+
 use libeldenring::prelude::*;
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::nudge_position::NudgePosition;
-use practice_tool_core::widgets::Widget;
+use practice_tool_core::widgets::nudge_position::{NudgePosition, NudgePositionStorage};
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::Value;
 
+use crate::remote::{Control, Shared};
 use crate::widgets::position::SavePosition;
 
+/// Horizontal unit vector the character faces, given the facing angle in
+/// radians. An angle of zero faces along +Z.
+pub(crate) fn facing_direction(angle: f32) -> [f32; 3] {
+    [angle.sin(), 0., angle.cos()]
+}
+
+/// Horizontal displacement for moving `forward` units along the facing
+/// direction and `right` units to the right of it.
+pub(crate) fn facing_offset(angle: f32, forward: f32, right: f32) -> [f32; 3] {
+    let [fx, _, fz] = facing_direction(angle);
+    [fx * forward + fz * right, 0., fz * forward - fx * right]
+}
+
+/// Rotates an angle in radians by `degrees`, wrapping into `[-π, π)`.
+pub(crate) fn rotate_angle(angle: f32, degrees: f32) -> f32 {
+    (angle + degrees.to_radians() + PI).rem_euclid(2. * PI) - PI
+}
+
+/// Hotkeys and step sizes for the facing-relative nudges.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FacingNudges {
+    pub(crate) step: f32,
+    pub(crate) forward: Option<Key>,
+    pub(crate) back: Option<Key>,
+    pub(crate) left: Option<Key>,
+    pub(crate) right: Option<Key>,
+    pub(crate) rotate_step: f32,
+    pub(crate) rotate_left: Option<Key>,
+    pub(crate) rotate_right: Option<Key>,
+}
+
+#[derive(Clone, Copy)]
+enum FacingNudge {
+    Forward,
+    Back,
+    Left,
+    Right,
+    RotateLeft,
+    RotateRight,
+}
+
+impl FacingNudge {
+    const ALL: [FacingNudge; 6] = [
+        FacingNudge::Forward,
+        FacingNudge::Back,
+        FacingNudge::Left,
+        FacingNudge::Right,
+        FacingNudge::RotateLeft,
+        FacingNudge::RotateRight,
+    ];
+
+    /// Remote control action.
+    fn action(&self) -> &'static str {
+        match self {
+            FacingNudge::Forward => "forward",
+            FacingNudge::Back => "back",
+            FacingNudge::Left => "left",
+            FacingNudge::Right => "right",
+            FacingNudge::RotateLeft => "rotate_left",
+            FacingNudge::RotateRight => "rotate_right",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            FacingNudge::Forward => "Nudge forward",
+            FacingNudge::Back => "Nudge back",
+            FacingNudge::Left => "Nudge left",
+            FacingNudge::Right => "Nudge right",
+            FacingNudge::RotateLeft => "Rotate left",
+            FacingNudge::RotateRight => "Rotate right",
+        }
+    }
+}
+
+struct Nudge {
+    vertical: NudgePosition<Shared<SavePosition>>,
+    position: Shared<SavePosition>,
+    chunk_position: Position,
+    torrent_chunk_position: Position,
+    nudges: FacingNudges,
+    labels: Vec<(FacingNudge, String)>,
+}
+
+impl Nudge {
+    fn key(&self, nudge: FacingNudge) -> Option<&Key> {
+        match nudge {
+            FacingNudge::Forward => self.nudges.forward.as_ref(),
+            FacingNudge::Back => self.nudges.back.as_ref(),
+            FacingNudge::Left => self.nudges.left.as_ref(),
+            FacingNudge::Right => self.nudges.right.as_ref(),
+            FacingNudge::RotateLeft => self.nudges.rotate_left.as_ref(),
+            FacingNudge::RotateRight => self.nudges.rotate_right.as_ref(),
+        }
+    }
+
+    fn apply(&self, nudge: FacingNudge) {
+        let step = self.nudges.step;
+        let (forward, right, degrees) = match nudge {
+            FacingNudge::Forward => (step, 0., 0.),
+            FacingNudge::Back => (-step, 0., 0.),
+            FacingNudge::Left => (0., -step, 0.),
+            FacingNudge::Right => (0., step, 0.),
+            FacingNudge::RotateLeft => (0., 0., -self.nudges.rotate_step),
+            FacingNudge::RotateRight => (0., 0., self.nudges.rotate_step),
+        };
+
+        let Some(angle) = self.chunk_position.angle1.read() else {
+            return;
+        };
+
+        let [dx, _, dz] = facing_offset(angle, forward, right);
+
+        for position in [&self.chunk_position, &self.torrent_chunk_position] {
+            if let Some([x, _, z, r1, _]) = position.read() {
+                if degrees == 0. {
+                    position.x.write(x + dx);
+                    position.z.write(z + dz);
+                } else {
+                    // Only `angle1` is known to be the yaw; the second angle is
+                    // restored verbatim by saved positions and left alone here.
+                    position.angle1.write(rotate_angle(r1, degrees));
+                }
+            }
+        }
+    }
+}
+
+impl Widget for Nudge {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.vertical.render(ui);
+
+        let button_width = BUTTON_WIDTH * scaling_factor(ui);
+        for (nudge, label) in &self.labels {
+            if ui.button_with_size(label, [button_width, BUTTON_HEIGHT]) {
+                self.apply(*nudge);
+            }
+        }
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.vertical.render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.vertical.interact(ui);
+
+        for nudge in FacingNudge::ALL {
+            if self.key(nudge).map(|k| k.is_pressed(ui)).unwrap_or(false) {
+                self.apply(nudge);
+            }
+        }
+    }
+}
+
+impl Control for Nudge {
+    fn label(&self) -> String {
+        "Nudge position".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["up", "down", "forward", "back", "left", "right", "rotate_left", "rotate_right"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        match action {
+            "up" => self.position.lock().nudge_up(),
+            "down" => self.position.lock().nudge_down(),
+            _ => {
+                let nudge = FacingNudge::ALL.into_iter().find(|n| n.action() == action);
+                self.apply(nudge.ok_or(format!("Unknown nudge {action}"))?);
+            },
+        }
+        Ok(())
+    }
+}
+
 pub(crate) fn nudge_position(
     global_position: Position,
     chunk_position: Position,
@@ -12,10 +193,63 @@ pub(crate) fn nudge_position(
     nudge: f32,
     key_nudge_up: Option<Key>,
     key_nudge_down: Option<Key>,
-) -> Box<dyn Widget> {
-    Box::new(NudgePosition::new(
-        SavePosition::new(global_position, chunk_position, torrent_chunk_position, nudge),
-        key_nudge_up,
-        key_nudge_down,
-    ))
+    nudges: FacingNudges,
+) -> impl Widget + Control {
+    let position = Shared::new(SavePosition::new(
+        global_position,
+        chunk_position.clone(),
+        torrent_chunk_position.clone(),
+        nudge,
+        None,
+    ));
+    let vertical = NudgePosition::new(position.clone(), key_nudge_up, key_nudge_down);
+
+    let mut widget = Nudge {
+        vertical,
+        position,
+        chunk_position,
+        torrent_chunk_position,
+        nudges,
+        labels: Vec::new(),
+    };
+
+    widget.labels = FacingNudge::ALL
+        .into_iter()
+        .filter_map(|nudge| widget.key(nudge).map(|k| (nudge, format!("{} ({k})", nudge.name()))))
+        .collect();
+
+    widget
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        for (a, b) in a.into_iter().zip(b) {
+            assert!((a - b).abs() < 1e-5, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn test_facing_offset() {
+        assert_close(facing_offset(0., 1., 0.), [0., 0., 1.]);
+        assert_close(facing_offset(0., 0., 1.), [1., 0., 0.]);
+        assert_close(facing_offset(FRAC_PI_2, 2., 0.), [2., 0., 0.]);
+        assert_close(facing_offset(FRAC_PI_2, 0., 1.), [0., 0., -1.]);
+        assert_close(facing_offset(PI, -1., 0.), [0., 0., 1.]);
+        assert_close(facing_offset(FRAC_PI_2, 1., -1.), [1., 0., 1.]);
+    }
+
+    #[test]
+    fn test_rotate_angle() {
+        assert!((rotate_angle(0., 90.) - FRAC_PI_2).abs() < 1e-5);
+        assert!((rotate_angle(0., -90.) + FRAC_PI_2).abs() < 1e-5);
+        assert!((rotate_angle(FRAC_PI_2, 180.) + FRAC_PI_2).abs() < 1e-5);
+        assert!((rotate_angle(-PI + 0.1, -45.) - (PI + 0.1 - 45f32.to_radians())).abs() < 1e-5);
+        assert!((rotate_angle(1., 360.) - 1.).abs() < 1e-5);
+        assert!((rotate_angle(1., 0.) - 1.).abs() < 1e-5);
+    }
 }
@@ -0,0 +1,172 @@
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::{json, Value};
+
+use super::{begin_popup, close_button, popup_anchor};
+use crate::remote::{arg, select, Control};
+
+const POPUP_TAG: &str = "##player-vitals";
+
+struct Vital {
+    name: &'static str,
+    ptr: PointerChain<[u32; 3]>,
+    value: i32,
+    percent: f32,
+}
+
+impl Vital {
+    fn new(name: &'static str, ptr: PointerChain<[u32; 3]>) -> Self {
+        Vital { name, ptr, value: 0, percent: 100. }
+    }
+
+    fn set(&self, value: u32) {
+        if let Some([_, base_max, max]) = self.ptr.read() {
+            self.ptr.write([value.min(max), base_max, max]);
+        }
+    }
+
+    fn refill(&self) {
+        if let Some([_, _, max]) = self.ptr.read() {
+            self.set(max);
+        }
+    }
+
+    fn set_percent(&self, percent: f32) {
+        if let Some([_, _, max]) = self.ptr.read() {
+            self.set((max as f32 * percent.clamp(0., 100.) / 100.).round() as u32);
+        }
+    }
+}
+
+/// Edits the player's current HP, FP and stamina.
+pub(crate) struct PlayerVitals {
+    label_close: String,
+    hotkey_refill: Option<Key>,
+    hotkey_close: Key,
+    vitals: [Vital; 3],
+}
+
+impl PlayerVitals {
+    pub(crate) fn new(
+        hp: PointerChain<[u32; 3]>,
+        fp: PointerChain<[u32; 3]>,
+        sp: PointerChain<[u32; 3]>,
+        hotkey_refill: Option<Key>,
+        hotkey_close: Key,
+    ) -> Self {
+        PlayerVitals {
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_refill,
+            hotkey_close,
+            vitals: [Vital::new("HP", hp), Vital::new("FP", fp), Vital::new("Stamina", sp)],
+        }
+    }
+
+    fn refill(&self) {
+        self.vitals.iter().for_each(Vital::refill);
+    }
+}
+
+impl Widget for PlayerVitals {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size("HP/FP/Stamina", [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            for vital in self.vitals.iter_mut() {
+                let _id = ui.push_id(vital.name);
+
+                let Some([current, _, max]) = vital.ptr.read() else {
+                    ui.text(format!("{}: not available", vital.name));
+                    continue;
+                };
+
+                ui.text(format!("{} {current}/{max}", vital.name));
+
+                {
+                    let _tok = ui.push_item_width(150.);
+                    if !ui.is_any_item_active() {
+                        vital.value = current as i32;
+                    }
+                    if ui.input_int("##value", &mut vital.value).enter_returns_true(true).build() {
+                        vital.set(vital.value.max(0) as u32);
+                    }
+                }
+
+                ui.same_line();
+                if ui.button("Max") {
+                    vital.refill();
+                }
+
+                {
+                    let _tok = ui.push_item_width(150.);
+                    ui.slider_config("##percent", 0., 100.)
+                        .display_format("%.0f%%")
+                        .build(&mut vital.percent);
+                }
+
+                ui.same_line();
+                if ui.button("Set %") {
+                    vital.set_percent(vital.percent);
+                }
+
+                ui.separator();
+            }
+
+            if ui.button_with_size("Refill all", [300., button_height]) {
+                self.refill();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 300.);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if self.hotkey_refill.as_ref().map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.refill();
+        }
+    }
+}
+
+/// `set` takes a vital and a value: `{ "name": "HP", "value": 100 }`.
+impl Control for PlayerVitals {
+    fn label(&self) -> String {
+        "HP/FP/Stamina".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["refill", "set"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "refill" {
+            self.refill();
+            return Ok(());
+        }
+
+        let vital = &self.vitals[select(&self.vitals, |v| v.name, args)?];
+        vital.ptr.read().ok_or(format!("{} can't be read right now", vital.name))?;
+        vital.set(arg(args, "value")?);
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        let vitals = self
+            .vitals
+            .iter()
+            .map(|v| {
+                let value = v.ptr.read().map(|[cur, _, max]| json!({ "current": cur, "max": max }));
+                (v.name.to_string(), value.unwrap_or(Value::Null))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        Value::Object(vitals)
+    }
+}
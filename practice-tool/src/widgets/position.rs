@@ -1,10 +1,15 @@
 use std::fmt::Write;
 
-use libeldenring::prelude::Position as ErPosition;
+use libeldenring::prelude::{MapId, Position as ErPosition};
+use practice_tool_core::crossbeam_channel::Sender;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::nudge_position::NudgePositionStorage;
 use practice_tool_core::widgets::position::{Position, PositionStorage};
 use practice_tool_core::widgets::Widget;
+use serde_json::{json, Value};
+
+use crate::remote::{Control, Shared};
+use crate::widgets::warp::LocationWarp;
 
 pub(super) struct SavePosition {
     global_position: ErPosition,
@@ -18,6 +23,8 @@ pub(super) struct SavePosition {
 
     saved_position: [f32; 5],
     saved_map_id: u32,
+
+    warp: Option<LocationWarp>,
 }
 
 impl SavePosition {
@@ -26,6 +33,7 @@ impl SavePosition {
         chunk_position: ErPosition,
         torrent_chunk_position: ErPosition,
         nudge: f32,
+        warp: Option<LocationWarp>,
     ) -> Self {
         Self {
             global_position,
@@ -37,8 +45,19 @@ impl SavePosition {
             label_stored: String::new(),
             valid: false,
             nudge,
+            warp,
         }
     }
+
+    /// The stored position and its map, if one was saved successfully.
+    pub(super) fn saved(&self) -> Option<(MapId, [f32; 5])> {
+        self.valid.then_some((MapId(self.saved_map_id), self.saved_position))
+    }
+
+    pub(super) fn set_saved(&mut self, map_id: MapId, position: [f32; 5]) {
+        self.saved_map_id = map_id.0;
+        self.saved_position = position;
+    }
 }
 
 impl PositionStorage for SavePosition {
@@ -57,22 +76,22 @@ impl PositionStorage for SavePosition {
     }
 
     fn load(&mut self) {
-        if let (Some([gx, gy, gz, _, _]), Some([cx, cy, cz, _, _])) =
-            (self.global_position.read(), self.chunk_position.read())
-        {
-            let [sx, sy, sz, sr1, sr2] = self.saved_position;
-            self.chunk_position.write([sx - gx + cx, sy - gy + cy, sz - gz + cz, sr1, sr2]);
-            self.chunk_position.write_map_id(self.saved_map_id);
-
-            if let Some([tcx, tcy, tcz, _, _]) = self.torrent_chunk_position.read() {
-                self.torrent_chunk_position.write([
-                    sx - gx + tcx,
-                    sy - gy + tcy,
-                    sz - gz + tcz,
-                    sr1,
-                    sr2,
-                ]);
-            }
+        let current_map_id = self.global_position.read_map_id().map(MapId);
+        let saved_map_id = MapId(self.saved_map_id);
+
+        match (&self.warp, current_map_id) {
+            (Some(warp), Some(map_id))
+                if self.saved_map_id != 0 && !map_id.same_map(&saved_map_id) =>
+            {
+                warp.warp_to(saved_map_id, self.saved_position);
+            },
+            _ => teleport(
+                &self.global_position,
+                &self.chunk_position,
+                &self.torrent_chunk_position,
+                self.saved_map_id,
+                self.saved_position,
+            ),
         }
     }
 
@@ -134,16 +153,166 @@ impl NudgePositionStorage for SavePosition {
     }
 }
 
+/// Translates a target in global coordinates to the chunk coordinate space,
+/// given the current global and chunk positions of the same entity.
+pub(crate) fn chunk_target(
+    [tx, ty, tz]: [f32; 3],
+    [gx, gy, gz]: [f32; 3],
+    [cx, cy, cz]: [f32; 3],
+) -> [f32; 3] {
+    [tx - gx + cx, ty - gy + cy, tz - gz + cz]
+}
+
+/// Moves the player to a position expressed in global coordinates. The
+/// position is applied as a delta to the chunk position, so the target map has
+/// to be loaded already.
+pub(crate) fn teleport(
+    global_position: &ErPosition,
+    chunk_position: &ErPosition,
+    torrent_chunk_position: &ErPosition,
+    map_id: u32,
+    [sx, sy, sz, sr1, sr2]: [f32; 5],
+) {
+    if let (Some([gx, gy, gz, _, _]), Some([cx, cy, cz, _, _])) =
+        (global_position.read(), chunk_position.read())
+    {
+        let target = [sx, sy, sz];
+        let global = [gx, gy, gz];
+
+        let [x, y, z] = chunk_target(target, global, [cx, cy, cz]);
+        chunk_position.write([x, y, z, sr1, sr2]);
+        chunk_position.write_map_id(map_id);
+
+        if let Some([tcx, tcy, tcz, _, _]) = torrent_chunk_position.read() {
+            let [x, y, z] = chunk_target(target, global, [tcx, tcy, tcz]);
+            torrent_chunk_position.write([x, y, z, sr1, sr2]);
+        }
+    }
+}
+
+/// Position storage over a shared slot. The labels are copied out so they
+/// can be borrowed without holding the lock.
+struct SharedSavePosition {
+    slot: Shared<SavePosition>,
+    label_current: String,
+    label_stored: String,
+}
+
+impl PositionStorage for SharedSavePosition {
+    fn save(&mut self) {
+        self.slot.lock().save();
+    }
+
+    fn load(&mut self) {
+        self.slot.lock().load();
+    }
+
+    fn display_current(&mut self) -> &str {
+        self.label_current.clear();
+        self.label_current.push_str(self.slot.lock().display_current());
+        &self.label_current
+    }
+
+    fn display_stored(&mut self) -> &str {
+        self.label_stored.clear();
+        self.label_stored.push_str(self.slot.lock().display_stored());
+        &self.label_stored
+    }
+
+    fn is_valid(&self) -> bool {
+        self.slot.lock().is_valid()
+    }
+}
+
+/// Saved position that can be loaded from anywhere in the world, warping to
+/// the saved map first if needed.
+struct WarpingSavePosition {
+    position: Position<SharedSavePosition>,
+    slot: Shared<SavePosition>,
+    warp: LocationWarp,
+}
+
+impl Widget for WarpingSavePosition {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.position.render(ui);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.position.render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.warp.update();
+        self.position.interact(ui);
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.warp.log(&tx);
+        self.position.log(tx);
+    }
+}
+
+impl Control for WarpingSavePosition {
+    fn label(&self) -> String {
+        "Position".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["save", "load"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        let mut slot = self.slot.lock();
+        match action {
+            "save" => slot.save(),
+            _ if slot.saved().is_none() => return Err("No position saved".to_string()),
+            _ => slot.load(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        let saved = self.slot.lock().saved().map(|(map_id, [x, y, z, ..])| {
+            json!({ "map_id": map_id.to_string(), "position": [x, y, z] })
+        });
+        json!({ "saved": saved })
+    }
+}
+
 pub(crate) fn save_position(
     global_position: ErPosition,
     chunk_position: ErPosition,
     torrent_chunk_position: ErPosition,
+    warp: LocationWarp,
     key_load: Option<Key>,
     key_save: Option<Key>,
-) -> Box<dyn Widget> {
-    Box::new(Position::new(
-        SavePosition::new(global_position, chunk_position, torrent_chunk_position, 0.0),
-        key_load,
-        key_save,
-    ))
+) -> impl Widget + Control {
+    let slot = Shared::new(SavePosition::new(
+        global_position,
+        chunk_position,
+        torrent_chunk_position,
+        0.0,
+        Some(warp.clone()),
+    ));
+    let storage = SharedSavePosition {
+        slot: slot.clone(),
+        label_current: String::new(),
+        label_stored: String::new(),
+    };
+
+    WarpingSavePosition { position: Position::new(storage, key_load, key_save), slot, warp }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::chunk_target;
+
+    #[test]
+    fn test_chunk_target() {
+        assert_eq!(chunk_target([10., 20., 30.], [10., 20., 30.], [1., 2., 3.]), [1., 2., 3.]);
+        assert_eq!(chunk_target([15., 18., 30.], [10., 20., 30.], [1., 2., 3.]), [6., 0., 3.]);
+        assert_eq!(chunk_target([-100., 0., 250.], [100., 50., -50.], [-12.5, 7.25, 0.]), [
+            -212.5, -42.75, 300.
+        ]);
+    }
 }
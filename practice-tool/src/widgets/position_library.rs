@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use imgui::{Condition, InputText, TreeNodeFlags};
+use libeldenring::prelude::{MapId, Position as ErPosition};
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::position::PositionStorage;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::position::SavePosition;
+use super::warp::LocationWarp;
+use super::{begin_popup, close_button, load_file, popup_anchor, string_match, write_file};
+use crate::remote::{arg, select, Control};
+use crate::util::get_dll_relative_path;
+
+const POPUP_TAG: &str = "##position-library";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LibraryEntry {
+    name: String,
+    map_id: MapId,
+    position: [f32; 3],
+    rotation: [f32; 2],
+}
+
+impl LibraryEntry {
+    fn position(&self) -> [f32; 5] {
+        let [x, y, z] = self.position;
+        let [r1, r2] = self.rotation;
+        [x, y, z, r1, r2]
+    }
+
+    /// Entries are filtered by name and map id.
+    fn matches(&self, filter: &str) -> bool {
+        string_match(filter, &self.name) || string_match(filter, &self.map_id.to_string())
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct LibraryFile {
+    #[serde(default)]
+    positions: Vec<LibraryEntry>,
+}
+
+impl LibraryFile {
+    fn parse(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|e| format!("Couldn't parse positions: {e}"))
+    }
+
+    fn to_string(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| format!("Couldn't serialize positions: {e}"))
+    }
+}
+
+/// Named positions, persisted to `positions.toml` next to the DLL.
+pub(crate) struct PositionLibrary {
+    label: String,
+    label_close: String,
+    hotkey_load: Option<Key>,
+    hotkey_close: Key,
+
+    storage: SavePosition,
+    warp: LocationWarp,
+
+    path: Option<PathBuf>,
+    entries: Vec<LibraryEntry>,
+    visible: Vec<bool>,
+    selected: Option<usize>,
+    filter_string: String,
+    new_name: String,
+
+    logs: Vec<String>,
+}
+
+impl PositionLibrary {
+    pub(crate) fn new(
+        global_position: ErPosition,
+        chunk_position: ErPosition,
+        torrent_chunk_position: ErPosition,
+        warp: LocationWarp,
+        hotkey_load: Option<Key>,
+        hotkey_close: Key,
+    ) -> Self {
+        let label = match &hotkey_load {
+            Some(k) => format!("Position library ({k})"),
+            None => "Position library".to_string(),
+        };
+        let label_close = format!("Close ({hotkey_close})");
+
+        let path = get_dll_relative_path("positions.toml");
+
+        let mut logs = Vec::new();
+        let entries = load_file::<LibraryFile>(path.as_deref(), &mut logs).positions;
+
+        let visible = vec![true; entries.len()];
+
+        PositionLibrary {
+            label,
+            label_close,
+            hotkey_load,
+            hotkey_close,
+            storage: SavePosition::new(
+                global_position,
+                chunk_position,
+                torrent_chunk_position,
+                0.0,
+                Some(warp.clone()),
+            ),
+            warp,
+            path,
+            entries,
+            visible,
+            selected: None,
+            filter_string: String::new(),
+            new_name: String::new(),
+            logs,
+        }
+    }
+
+    fn write_file(&mut self) {
+        let file = LibraryFile { positions: self.entries.clone() };
+        write_file(self.path.as_deref(), &file, &mut self.logs);
+    }
+
+    fn update_filter(&mut self) {
+        self.visible = self.entries.iter().map(|e| e.matches(&self.filter_string)).collect();
+    }
+
+    fn save_current(&mut self) {
+        self.storage.save();
+        let Some((map_id, [x, y, z, r1, r2])) = self.storage.saved() else {
+            self.logs.push("Couldn't read current position".to_string());
+            return;
+        };
+
+        let name = match self.new_name.trim() {
+            "" => format!("Position {}", self.entries.len() + 1),
+            name => name.to_string(),
+        };
+
+        self.logs.push(format!("Saved \"{name}\" on {map_id}"));
+        self.entries.push(LibraryEntry { name, map_id, position: [x, y, z], rotation: [r1, r2] });
+        self.selected = Some(self.entries.len() - 1);
+        self.new_name.clear();
+        self.update_filter();
+        self.write_file();
+    }
+
+    fn load_selected(&mut self) {
+        let Some(entry) = self.selected.and_then(|idx| self.entries.get(idx)) else {
+            return;
+        };
+
+        self.storage.set_saved(entry.map_id, entry.position());
+        self.storage.load();
+    }
+
+    fn delete_selected(&mut self) {
+        if let Some(idx) = self.selected.take().filter(|&idx| idx < self.entries.len()) {
+            let entry = self.entries.remove(idx);
+            self.logs.push(format!("Deleted \"{}\"", entry.name));
+            self.update_filter();
+            self.write_file();
+        }
+    }
+
+    fn export(&mut self, ui: &imgui::Ui) {
+        let positions = self
+            .entries
+            .iter()
+            .zip(self.visible.iter())
+            .filter(|(_, &visible)| visible)
+            .map(|(e, _)| e.clone())
+            .collect::<Vec<_>>();
+        let count = positions.len();
+
+        match (LibraryFile { positions }).to_string() {
+            Ok(content) => {
+                ui.set_clipboard_text(content);
+                self.logs.push(format!("Exported {count} positions to clipboard"));
+            },
+            Err(e) => self.logs.push(e),
+        }
+    }
+
+    fn import(&mut self, ui: &imgui::Ui) {
+        let Some(content) = ui.clipboard_text() else {
+            self.logs.push("Clipboard is empty".to_string());
+            return;
+        };
+
+        match LibraryFile::parse(&content) {
+            Ok(file) => {
+                let count = file.positions.len();
+                for entry in file.positions {
+                    if !self.entries.contains(&entry) {
+                        self.entries.push(entry);
+                    }
+                }
+                self.logs.push(format!("Imported {count} positions from clipboard"));
+                self.update_filter();
+                self.write_file();
+            },
+            Err(e) => self.logs.push(e),
+        }
+    }
+}
+
+impl Widget for PositionLibrary {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            self.update_filter();
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            let _tok = ui.push_item_width(-1.);
+
+            if InputText::new(ui, "##position-library-filter", &mut self.filter_string)
+                .hint("Filter...")
+                .build()
+            {
+                self.update_filter();
+            }
+
+            let filtered = !self.filter_string.is_empty();
+
+            let mut maps: BTreeMap<MapId, Vec<usize>> = BTreeMap::new();
+            for (idx, entry) in self.entries.iter().enumerate() {
+                if self.visible.get(idx).copied().unwrap_or(true) {
+                    maps.entry(entry.map_id).or_default().push(idx);
+                }
+            }
+
+            ui.child_window("##position-library-entries").size([400., 300.]).build(|| {
+                for (map_id, indices) in maps {
+                    let map_label = map_id.to_string();
+                    let node = ui.tree_node_config(&map_label);
+                    let node = if filtered { node.opened(true, Condition::Always) } else { node };
+
+                    node.flags(TreeNodeFlags::SPAN_AVAIL_WIDTH).build(|| {
+                        for idx in indices {
+                            let entry = &self.entries[idx];
+                            let [x, y, z] = entry.position;
+                            let label = format!("{} ({x:.1} {y:.1} {z:.1})##{idx}", entry.name);
+
+                            if ui
+                                .selectable_config(&label)
+                                .selected(self.selected == Some(idx))
+                                .build()
+                            {
+                                self.selected = Some(idx);
+                            }
+                        }
+                    });
+                }
+            });
+
+            InputText::new(ui, "##position-library-name", &mut self.new_name)
+                .hint("Name...")
+                .build();
+
+            if ui.button_with_size("Save current position", [400., button_height]) {
+                self.save_current();
+            }
+
+            let half_width = 196.;
+            if ui.button_with_size("Load", [half_width, button_height]) {
+                self.load_selected();
+            }
+            ui.same_line();
+            if ui.button_with_size("Delete", [half_width, button_height]) {
+                self.delete_selected();
+            }
+
+            if ui.button_with_size("Export to clipboard", [half_width, button_height]) {
+                self.export(ui);
+            }
+            ui.same_line();
+            if ui.button_with_size("Import from clipboard", [half_width, button_height]) {
+                self.import(ui);
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.warp.update();
+
+        if ui.is_any_item_active() {
+            return;
+        }
+
+        if self.hotkey_load.as_ref().map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.load_selected();
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.warp.log(&tx);
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+/// `save` takes an optional name, `load` and `delete` the name of a
+/// position: `{ "name": "Margit arena" }`.
+impl Control for PositionLibrary {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["save", "load", "delete"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "save" {
+            self.new_name = arg::<Option<String>>(args, "name")?.unwrap_or_default();
+            self.save_current();
+            return Ok(());
+        }
+
+        self.selected = Some(select(&self.entries, |e| &e.name, args)?);
+        match action {
+            "load" => self.load_selected(),
+            _ => self.delete_selected(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({ "positions": self.entries.iter().map(|e| &e.name).collect::<Vec<_>>() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, map_id: MapId) -> LibraryEntry {
+        LibraryEntry { name: name.to_string(), map_id, position: [1., 2., 3.], rotation: [0.5, 0.] }
+    }
+
+    #[test]
+    fn test_library_file() {
+        let file = LibraryFile {
+            positions: vec![
+                entry("Margit", MapId::new(10, 0, 0, 0)),
+                entry("Agheel Lake", MapId::new(60, 42, 36, 0)),
+            ],
+        };
+
+        let content = file.to_string().unwrap();
+        assert_eq!(LibraryFile::parse(&content).unwrap(), file);
+        assert_eq!(LibraryFile::parse("").unwrap(), LibraryFile::default());
+        assert!(LibraryFile::parse("positions = 3").is_err());
+        assert_eq!(file.positions[0].position(), [1., 2., 3., 0.5, 0.]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let margit = entry("Margit arena", MapId::new(10, 0, 0, 0));
+        assert!(margit.matches(""));
+        assert!(margit.matches("margit"));
+        assert!(margit.matches("mgt"));
+        assert!(margit.matches("m10_00"));
+        assert!(!margit.matches("godrick"));
+        assert!(!margit.matches("m60"));
+    }
+}
@@ -1,14 +1,16 @@
 use libeldenring::prelude::*;
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::store_value::{ReadWrite, StoreValue};
+use practice_tool_core::widgets::store_value::ReadWrite;
 use practice_tool_core::widgets::Widget;
 
-struct Quitout {
+use crate::remote::{Control, ValueCommand};
+
+pub(crate) struct Quitout {
     ptr: PointerChain<u8>,
 }
 
 impl Quitout {
-    fn new(ptr: PointerChain<u8>) -> Self {
+    pub(crate) fn new(ptr: PointerChain<u8>) -> Self {
         Self { ptr }
     }
 }
@@ -27,6 +29,6 @@ impl ReadWrite for Quitout {
     }
 }
 
-pub(crate) fn quitout(ptr: PointerChain<u8>, key: Option<Key>) -> Box<dyn Widget> {
-    Box::new(StoreValue::new(Quitout::new(ptr), key))
+pub(crate) fn quitout(ptr: PointerChain<u8>, key: Option<Key>) -> impl Widget + Control {
+    ValueCommand::new(Quitout::new(ptr), key)
 }
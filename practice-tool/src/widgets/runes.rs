@@ -1,9 +1,11 @@
 use libeldenring::prelude::*;
 use practice_tool_core::key::Key;
-use practice_tool_core::widgets::store_value::{ReadWrite, StoreValue};
+use practice_tool_core::widgets::store_value::ReadWrite;
 use practice_tool_core::widgets::Widget;
 
-struct Runes {
+use crate::remote::{Control, ValueCommand};
+
+pub(crate) struct Runes {
     ptr: PointerChain<u32>,
     current: u32,
     amount: u32,
@@ -11,7 +13,7 @@ struct Runes {
 }
 
 impl Runes {
-    fn new(amount: u32, ptr: PointerChain<u32>) -> Self {
+    pub(crate) fn new(amount: u32, ptr: PointerChain<u32>) -> Self {
         Self { ptr, current: 0, amount, label: format!("Add {amount} runes") }
     }
 }
@@ -35,6 +37,10 @@ impl ReadWrite for Runes {
     }
 }
 
-pub(crate) fn runes(amount: u32, ptr: PointerChain<u32>, key: Option<Key>) -> Box<dyn Widget> {
-    Box::new(StoreValue::new(Runes::new(amount, ptr), key))
+pub(crate) fn runes(
+    amount: u32,
+    ptr: PointerChain<u32>,
+    key: Option<Key>,
+) -> impl Widget + Control {
+    ValueCommand::new(Runes::new(amount, ptr), key)
 }
@@ -1,12 +1,71 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
+use practice_tool_core::crossbeam_channel::Sender;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::savefile_manager::SavefileManager;
 use practice_tool_core::widgets::Widget;
+use serde_json::Value;
 
-pub(crate) fn savefile_manager(key_load: Option<Key>, key_close: Key) -> Box<dyn Widget> {
+use crate::remote::{arg, Control};
+
+/// The core savefile manager can't be driven from outside, so the remote
+/// `load` action copies a savefile over the game's the same way it does.
+struct SavefileCommand {
+    manager: SavefileManager,
+    path: PathBuf,
+}
+
+impl Widget for SavefileCommand {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.manager.render(ui);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.manager.render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.manager.interact(ui);
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.manager.log(tx);
+    }
+}
+
+/// `load` takes a savefile path relative to the game's savefile directory:
+/// `{ "name": "practice/margit.sl2" }`.
+impl Control for SavefileCommand {
+    fn label(&self) -> String {
+        "Savefile manager".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["load"]
+    }
+
+    fn run(&mut self, _: &str, args: &Value) -> Result<(), String> {
+        let name: &str = arg(args, "name")?;
+        let name = Path::new(name);
+        if !name.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("{} isn't in the savefile directory", name.display()));
+        }
+
+        let source = self.path.parent().map(|dir| dir.join(name)).unwrap_or_default();
+        if source == self.path {
+            return Err("Can't load the savefile over itself".to_string());
+        }
+
+        std::fs::copy(&source, &self.path)
+            .map(|_| ())
+            .map_err(|e| format!("Couldn't load {}: {e}", name.display()))
+    }
+}
+
+pub(crate) fn savefile_manager(key_load: Option<Key>, key_close: Key) -> impl Widget + Control {
     // TODO
-    Box::new(SavefileManager::new(key_load, Some(key_close), get_savefile_path().unwrap()))
+    let path = get_savefile_path().unwrap();
+    SavefileCommand { manager: SavefileManager::new(key_load, Some(key_close), path.clone()), path }
 }
 
 fn get_savefile_path() -> Result<PathBuf, String> {
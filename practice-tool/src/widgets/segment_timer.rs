@@ -0,0 +1,325 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use libeldenring::prelude::{EventFlags, PointerChain, Position as ErPosition};
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::position::PositionStorage;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::position::SavePosition;
+use super::split_timer::format_delta;
+use super::trigger::{Trigger, Watch};
+use super::warp::LocationWarp;
+use super::{load_file, write_file};
+use crate::remote::Control;
+use crate::util::{get_dll_relative_path, Igt};
+
+/// Attempt times of a segment, in milliseconds of IGT.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct SegmentHistory {
+    #[serde(default)]
+    attempts: Vec<u64>,
+}
+
+impl SegmentHistory {
+    fn best(&self) -> Option<u64> {
+        self.attempts.iter().copied().min()
+    }
+
+    fn average(&self) -> Option<u64> {
+        let count = self.attempts.len() as u64;
+        (count > 0).then(|| self.attempts.iter().sum::<u64>() / count)
+    }
+}
+
+/// File name of a segment's history, with anything that isn't safe in a
+/// file name replaced.
+fn history_file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') { c } else { '_' })
+        .collect::<String>();
+    format!("{}.toml", name.trim())
+}
+
+/// Times attempts at a segment, from a hotkey to a configurable end
+/// condition, and keeps the times of every attempt.
+pub(crate) struct SegmentTimer {
+    label_start: String,
+    label_stop: String,
+    name: String,
+    hotkey_start: Option<Key>,
+    hotkey_stop: Option<Key>,
+    hotkey_retry: Option<Key>,
+
+    igt: PointerChain<usize>,
+    event_flags: EventFlags,
+    global_position: ErPosition,
+    storage: SavePosition,
+
+    trigger: Trigger,
+    watch: Watch,
+    started: Option<u64>,
+    best_at_start: Option<u64>,
+    last: Option<u64>,
+
+    path: Option<PathBuf>,
+    history: SegmentHistory,
+    indicator: String,
+
+    logs: Vec<String>,
+}
+
+impl SegmentTimer {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        name: String,
+        trigger: Trigger,
+        igt: PointerChain<usize>,
+        event_flags: EventFlags,
+        global_position: ErPosition,
+        chunk_position: ErPosition,
+        torrent_chunk_position: ErPosition,
+        warp: LocationWarp,
+        hotkey_start: Option<Key>,
+        hotkey_stop: Option<Key>,
+        hotkey_retry: Option<Key>,
+    ) -> Self {
+        let label_start = match &hotkey_start {
+            Some(k) => format!("Start {name} ({k})"),
+            None => format!("Start {name}"),
+        };
+        let label_stop = match &hotkey_stop {
+            Some(k) => format!("Stop ({k})"),
+            None => "Stop".to_string(),
+        };
+
+        let path =
+            get_dll_relative_path("segments").map(|path| path.join(history_file_name(&name)));
+
+        let mut logs = Vec::new();
+        let history = load_file::<SegmentHistory>(path.as_deref(), &mut logs);
+
+        SegmentTimer {
+            label_start,
+            label_stop,
+            name,
+            hotkey_start,
+            hotkey_stop,
+            hotkey_retry,
+            igt,
+            event_flags,
+            global_position: global_position.clone(),
+            storage: SavePosition::new(
+                global_position,
+                chunk_position,
+                torrent_chunk_position,
+                0.0,
+                Some(warp),
+            ),
+            trigger,
+            watch: Watch::default(),
+            started: None,
+            best_at_start: None,
+            last: None,
+            path,
+            history,
+            indicator: String::new(),
+            logs,
+        }
+    }
+
+    fn write_file(&mut self) {
+        write_file(self.path.as_deref(), &self.history, &mut self.logs);
+    }
+
+    fn igt(&self) -> Option<u64> {
+        self.igt.read().map(|igt| igt as u64)
+    }
+
+    fn begin(&mut self) {
+        let Some(igt) = self.igt() else {
+            self.logs.push("Couldn't read IGT".to_string());
+            return;
+        };
+
+        self.started = Some(igt);
+        self.best_at_start = self.history.best();
+        self.last = None;
+        self.watch = Watch::default();
+    }
+
+    /// Marks the start of the segment at the player's current position.
+    fn start(&mut self) {
+        self.storage.save();
+        self.begin();
+        self.logs.push(format!("Started {}", self.name));
+    }
+
+    /// Goes back to the start of the segment and times a new attempt.
+    fn retry(&mut self) {
+        if self.storage.saved().is_none() {
+            self.logs.push(format!("Start {} before retrying it", self.name));
+            return;
+        }
+
+        self.storage.load();
+        self.begin();
+    }
+
+    fn stop(&mut self) {
+        let (Some(started), Some(igt)) = (self.started.take(), self.igt()) else {
+            return;
+        };
+
+        let time = igt.saturating_sub(started);
+        let best = self.best_at_start.map(|best| time < best).unwrap_or(true);
+        self.history.attempts.push(time);
+        self.last = Some(time);
+        self.write_file();
+
+        self.logs.push(format!(
+            "{}: {}{}",
+            self.name,
+            Igt(time as usize),
+            if best { " (best)" } else { "" }
+        ));
+    }
+
+    fn current(&self) -> Option<u64> {
+        match self.started {
+            Some(started) => self.igt().map(|igt| igt.saturating_sub(started)),
+            None => self.last,
+        }
+    }
+
+    fn update_indicator(&mut self) {
+        let current = self.current();
+        let fmt =
+            |ms: Option<u64>| ms.map(|ms| Igt(ms as usize).to_string()).unwrap_or("-".to_string());
+
+        self.indicator.clear();
+        write!(
+            self.indicator,
+            "{}: {} | best {} | avg {}",
+            self.name,
+            fmt(current),
+            fmt(self.history.best()),
+            fmt(self.history.average())
+        )
+        .ok();
+        if let (Some(current), Some(best)) = (current, self.best_at_start) {
+            write!(self.indicator, " | {}", format_delta(current as i64 - best as i64)).ok();
+        }
+    }
+}
+
+impl Widget for SegmentTimer {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        if ui.button_with_size(&self.label_start, [button_width * 0.5 - 4., button_height]) {
+            self.start();
+        }
+        ui.same_line();
+        if ui.button_with_size(&self.label_stop, [button_width * 0.5 - 4., button_height]) {
+            self.stop();
+        }
+
+        self.update_indicator();
+        ui.text(&self.indicator);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        if self.started.is_some() || self.last.is_some() {
+            self.update_indicator();
+            ui.text(&self.indicator);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if self.hotkey_start.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.start();
+        }
+        if self.hotkey_retry.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.retry();
+        }
+        if self.hotkey_stop.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.stop();
+        }
+
+        if self.started.is_some() {
+            let reading = self.trigger.read(&self.event_flags, &self.global_position);
+            if self.watch.observe(&self.trigger, reading) {
+                self.stop();
+            }
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+impl Control for SegmentTimer {
+    fn label(&self) -> String {
+        self.name.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["start", "stop", "retry"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        match action {
+            "start" => self.start(),
+            "stop" => self.stop(),
+            _ if self.storage.saved().is_none() => {
+                return Err(format!("Start {} before retrying it", self.name));
+            },
+            _ => self.retry(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({
+            "running": self.started.is_some(),
+            "current": self.current(),
+            "best": self.history.best(),
+            "average": self.history.average(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let mut history = SegmentHistory::default();
+        assert_eq!(history.best(), None);
+        assert_eq!(history.average(), None);
+
+        history.attempts.extend([31_000, 29_500, 30_500]);
+        assert_eq!(history.best(), Some(29_500));
+        assert_eq!(history.average(), Some(30_333));
+
+        let content = toml::to_string(&history).unwrap();
+        assert_eq!(toml::from_str::<SegmentHistory>(&content).unwrap(), history);
+    }
+
+    #[test]
+    fn test_history_file_name() {
+        assert_eq!(history_file_name("Stormveil skip"), "Stormveil skip.toml");
+        assert_eq!(history_file_name("Margit/Godrick: any%"), "Margit_Godrick_ any_.toml");
+    }
+}
@@ -0,0 +1,540 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::trigger::{Trigger, TriggerSpec, Watch};
+use super::{load_file, write_file};
+use crate::livesplit::LiveSplit;
+use crate::remote::Control;
+use crate::util::{get_dll_relative_path, Igt};
+
+/// How often the game time is sent to LiveSplit while a run is going.
+const GAME_TIME_INTERVAL: Duration = Duration::from_millis(100);
+
+const COLOR_GOLD: [f32; 4] = [1., 0.84, 0., 1.];
+const COLOR_AHEAD: [f32; 4] = [0.3, 1., 0.3, 1.];
+const COLOR_BEHIND: [f32; 4] = [1., 0.3, 0.3, 1.];
+
+#[derive(Deserialize)]
+struct SplitSpec {
+    name: String,
+    #[serde(flatten)]
+    trigger: TriggerSpec,
+}
+
+/// A split as defined in the config.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "SplitSpec")]
+pub(crate) struct SplitDef {
+    name: String,
+    trigger: Trigger,
+}
+
+impl TryFrom<SplitSpec> for SplitDef {
+    type Error = String;
+
+    fn try_from(spec: SplitSpec) -> Result<Self, Self::Error> {
+        let trigger =
+            Trigger::try_from(spec.trigger).map_err(|e| format!("Split \"{}\": {e}", spec.name))?;
+
+        Ok(SplitDef { name: spec.name, trigger })
+    }
+}
+
+/// Personal best and best segment of a split, in milliseconds of IGT.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SplitRecord {
+    name: String,
+    /// Time from the start of the run in the personal best run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pb: Option<u64>,
+    /// Fastest time of the segment alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gold: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SplitsFile {
+    #[serde(default)]
+    splits: Vec<SplitRecord>,
+}
+
+impl SplitsFile {
+    fn record(&self, name: &str) -> SplitRecord {
+        self.splits.iter().find(|r| r.name == name).cloned().unwrap_or_else(|| SplitRecord {
+            name: name.to_string(),
+            pb: None,
+            gold: None,
+        })
+    }
+
+    fn update(&mut self, records: &[SplitRecord]) {
+        for record in records {
+            match self.splits.iter_mut().find(|r| r.name == record.name) {
+                Some(r) => *r = record.clone(),
+                None => self.splits.push(record.clone()),
+            }
+        }
+    }
+}
+
+/// The run in progress, timed in milliseconds of IGT so loading screens
+/// don't count.
+#[derive(Debug)]
+struct Run {
+    records: Vec<SplitRecord>,
+    /// Personal best times as they were when the run started.
+    comparison: Vec<Option<u64>>,
+    start: Option<u64>,
+    times: Vec<u64>,
+    golds: Vec<bool>,
+}
+
+impl Run {
+    fn new(records: Vec<SplitRecord>) -> Self {
+        let comparison = records.iter().map(|r| r.pb).collect();
+        Run { records, comparison, start: None, times: Vec::new(), golds: Vec::new() }
+    }
+
+    fn is_running(&self) -> bool {
+        self.start.is_some() && self.times.len() < self.records.len()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.start.is_some() && self.times.len() == self.records.len()
+    }
+
+    fn current(&self) -> Option<usize> {
+        Some(self.times.len()).filter(|_| self.is_running())
+    }
+
+    fn start(&mut self, igt: u64) {
+        self.start = Some(igt);
+        self.comparison = self.records.iter().map(|r| r.pb).collect();
+        self.times.clear();
+        self.golds.clear();
+    }
+
+    fn reset(&mut self) {
+        self.start = None;
+        self.times.clear();
+        self.golds.clear();
+    }
+
+    fn elapsed(&self, igt: u64) -> u64 {
+        match (self.is_finished(), self.start) {
+            (true, _) => self.times.last().copied().unwrap_or(0),
+            (false, Some(start)) => igt.saturating_sub(start),
+            (false, None) => 0,
+        }
+    }
+
+    /// Whether IGT is now behind the last split, as happens when an older save
+    /// is loaded mid-run.
+    fn rewound(&self, igt: u64) -> bool {
+        match self.start {
+            Some(start) if self.is_running() => {
+                igt < start || igt - start < self.times.last().copied().unwrap_or(0)
+            },
+            _ => false,
+        }
+    }
+
+    /// Ends the current split, updating the best segment and, if the run is
+    /// over and faster than the personal best, the personal best.
+    fn split(&mut self, igt: u64) {
+        let Some(idx) = self.current() else {
+            return;
+        };
+
+        let time = self.elapsed(igt);
+        let segment = time.saturating_sub(self.times.last().copied().unwrap_or(0));
+        let record = &mut self.records[idx];
+        let gold = record.gold.map(|gold| segment < gold).unwrap_or(true);
+        if gold {
+            record.gold = Some(segment);
+        }
+        self.times.push(time);
+        self.golds.push(gold);
+
+        if self.is_finished() && self.records[idx].pb.map(|pb| time < pb).unwrap_or(true) {
+            for (record, &time) in self.records.iter_mut().zip(&self.times) {
+                record.pb = Some(time);
+            }
+        }
+    }
+
+    /// Difference from the personal best at a completed split.
+    fn delta(&self, idx: usize) -> Option<i64> {
+        let time = *self.times.get(idx)?;
+        let pb = (*self.comparison.get(idx)?)?;
+        Some(time as i64 - pb as i64)
+    }
+}
+
+pub(super) fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{sign}{}", Igt(delta.unsigned_abs() as usize))
+}
+
+/// Times a run against its splits, optionally driving LiveSplit.
+pub(crate) struct SplitTimer {
+    label: String,
+    hotkey: Option<Key>,
+    hotkey_reset: Option<Key>,
+
+    igt: PointerChain<usize>,
+    event_flags: EventFlags,
+    global_position: Position,
+    livesplit: Option<LiveSplit>,
+
+    names: Vec<String>,
+    triggers: Vec<Trigger>,
+    run: Run,
+    watch: Watch,
+    game_time_sent: Instant,
+
+    path: Option<PathBuf>,
+    file: SplitsFile,
+
+    logs: Vec<String>,
+}
+
+impl SplitTimer {
+    pub(crate) fn new(
+        splits: Vec<SplitDef>,
+        igt: PointerChain<usize>,
+        event_flags: EventFlags,
+        global_position: Position,
+        livesplit: Option<String>,
+        hotkey: Option<Key>,
+        hotkey_reset: Option<Key>,
+    ) -> Self {
+        let label = match &hotkey {
+            Some(k) => format!("Start/split ({k})"),
+            None => "Start/split".to_string(),
+        };
+
+        let path = get_dll_relative_path("splits.toml");
+
+        let mut logs = Vec::new();
+        let file = load_file::<SplitsFile>(path.as_deref(), &mut logs);
+
+        let triggers = splits
+            .iter()
+            .map(|split| {
+                split.trigger.clone().resolve().unwrap_or_else(|e| {
+                    logs.push(format!("{e}, split \"{}\" manually", split.name));
+                    Trigger::Manual
+                })
+            })
+            .collect();
+
+        let records = splits.iter().map(|split| file.record(&split.name)).collect();
+
+        SplitTimer {
+            label,
+            hotkey,
+            hotkey_reset,
+            igt,
+            event_flags,
+            global_position,
+            livesplit: livesplit.map(LiveSplit::new),
+            names: splits.into_iter().map(|split| split.name).collect(),
+            triggers,
+            run: Run::new(records),
+            watch: Watch::default(),
+            game_time_sent: Instant::now(),
+            path,
+            file,
+            logs,
+        }
+    }
+
+    fn write_file(&mut self) {
+        self.file.update(&self.run.records);
+        write_file(self.path.as_deref(), &self.file, &mut self.logs);
+    }
+
+    fn start(&mut self, igt: u64) {
+        if self.names.is_empty() {
+            self.logs.push("No splits configured".to_string());
+            return;
+        }
+
+        self.run.start(igt);
+        self.watch = Watch::default();
+        if let Some(livesplit) = &self.livesplit {
+            livesplit.start_timer();
+            livesplit.pause_game_time();
+            livesplit.set_game_time(Duration::ZERO);
+        }
+    }
+
+    fn split(&mut self, igt: u64) {
+        self.run.split(igt);
+        self.watch.clear_flag();
+
+        let time = Duration::from_millis(self.run.elapsed(igt));
+        if let Some(livesplit) = &self.livesplit {
+            livesplit.set_game_time(time);
+            livesplit.split();
+        }
+
+        if self.run.is_finished() {
+            self.logs.push(format!("Run finished in {}", Igt::from(time)));
+        }
+        self.write_file();
+    }
+
+    fn reset(&mut self) {
+        self.run.reset();
+        if let Some(livesplit) = &self.livesplit {
+            livesplit.reset();
+        }
+    }
+
+    fn start_or_split(&mut self) {
+        let Some(igt) = self.igt.read().map(|igt| igt as u64) else {
+            self.logs.push("Couldn't read IGT".to_string());
+            return;
+        };
+
+        if self.run.is_running() {
+            self.split(igt);
+        } else {
+            self.start(igt);
+        }
+    }
+
+    fn render_splits(&self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let igt = self.igt.read().map(|igt| igt as u64).unwrap_or(0);
+
+        for (idx, name) in self.names.iter().enumerate() {
+            ui.text(name);
+            ui.same_line_with_pos(160. * scale);
+
+            if let Some(&time) = self.run.times.get(idx) {
+                ui.text(Igt(time as usize).to_string());
+                if let Some(delta) = self.run.delta(idx) {
+                    ui.same_line_with_pos(240. * scale);
+                    let color = match (self.run.golds[idx], delta < 0) {
+                        (true, _) => COLOR_GOLD,
+                        (false, true) => COLOR_AHEAD,
+                        (false, false) => COLOR_BEHIND,
+                    };
+                    ui.text_colored(color, format_delta(delta));
+                }
+            } else if self.run.current() == Some(idx) {
+                ui.text(Igt(self.run.elapsed(igt) as usize).to_string());
+            } else {
+                let pb = self.run.comparison[idx];
+                ui.text(pb.map(|pb| Igt(pb as usize).to_string()).unwrap_or_default());
+            }
+        }
+    }
+}
+
+impl Widget for SplitTimer {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        if ui.button_with_size(&self.label, [button_width * 0.5 - 4., button_height]) {
+            self.start_or_split();
+        }
+        ui.same_line();
+        if ui.button_with_size("Reset splits", [button_width * 0.5 - 4., button_height]) {
+            self.reset();
+        }
+
+        self.render_splits(ui);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        if self.run.start.is_some() {
+            self.render_splits(ui);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if !ui.is_any_item_active() {
+            if self.hotkey.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+                self.start_or_split();
+            }
+            if self.hotkey_reset.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+                self.reset();
+            }
+        }
+
+        let Some(idx) = self.run.current() else {
+            return;
+        };
+        let Some(igt) = self.igt.read().map(|igt| igt as u64) else {
+            return;
+        };
+
+        if self.run.rewound(igt) {
+            self.logs.push("IGT went back past the last split, splits reset".to_string());
+            self.reset();
+            return;
+        }
+
+        let trigger = &self.triggers[idx];
+        let reading = trigger.read(&self.event_flags, &self.global_position);
+        if self.watch.observe(trigger, reading) {
+            self.split(igt);
+        }
+
+        if let Some(livesplit) = &self.livesplit {
+            if self.run.is_running() && self.game_time_sent.elapsed() >= GAME_TIME_INTERVAL {
+                livesplit.set_game_time(Duration::from_millis(self.run.elapsed(igt)));
+                self.game_time_sent = Instant::now();
+            }
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+impl Control for SplitTimer {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["split", "reset"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        match action {
+            "split" => self.start_or_split(),
+            _ => self.reset(),
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({
+            "running": self.run.is_running(),
+            "current": self.run.current().map(|idx| &self.names[idx]),
+            "times": self.run.times,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, pb: Option<u64>, gold: Option<u64>) -> SplitRecord {
+        SplitRecord { name: name.to_string(), pb, gold }
+    }
+
+    #[test]
+    fn test_split_defs() {
+        #[derive(Deserialize)]
+        struct Splits {
+            splits: Vec<SplitDef>,
+        }
+
+        let splits = toml::from_str::<Splits>(
+            r#"splits = [
+                { name = "Margit", boss = "Margit, the Fell Omen" },
+                { name = "Stormveil", map_id = "m10_00_00_00" },
+                { name = "Godrick", event_flag = 10000800 },
+                { name = "Manual" },
+            ]"#,
+        )
+        .unwrap()
+        .splits;
+
+        assert_eq!(splits[0].trigger, Trigger::Boss("Margit, the Fell Omen".to_string()));
+        assert_eq!(splits[1].trigger, Trigger::MapId(MapId::new(10, 0, 0, 0)));
+        assert_eq!(splits[2].trigger, Trigger::EventFlag(10000800));
+        assert_eq!(splits[3].trigger, Trigger::Manual);
+
+        assert!(toml::from_str::<Splits>(
+            r#"splits = [{ name = "Both", event_flag = 1, map_id = "m10_00_00_00" }]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let mut run =
+            Run::new(vec![record("a", Some(10_000), Some(9_000)), record("b", None, None)]);
+        assert!(!run.is_running());
+
+        run.start(100_000);
+        assert_eq!(run.current(), Some(0));
+        assert_eq!(run.elapsed(108_000), 8_000);
+
+        run.split(108_000);
+        assert_eq!(run.golds, [true]);
+        assert_eq!(run.records[0].gold, Some(8_000));
+        assert_eq!(run.delta(0), Some(-2_000));
+
+        run.split(120_000);
+        assert!(run.is_finished());
+        assert_eq!(run.golds, [true, true]);
+        assert_eq!(run.records[0].pb, Some(8_000));
+        assert_eq!(run.records[1].pb, Some(20_000));
+        assert_eq!(run.elapsed(500_000), 20_000);
+
+        // A slower run keeps both the personal best and the golds.
+        run.start(0);
+        run.split(9_000);
+        run.split(25_000);
+        assert_eq!(run.golds, [false, false]);
+        assert_eq!(run.delta(1), Some(5_000));
+        assert_eq!(run.records[1].pb, Some(20_000));
+        assert_eq!(run.records[1].gold, Some(12_000));
+    }
+
+    #[test]
+    fn test_splits_file() {
+        let mut file = SplitsFile { splits: vec![record("a", Some(1), Some(1))] };
+        file.update(&[record("a", Some(2), None), record("b", None, Some(3))]);
+        assert_eq!(file.record("a"), record("a", Some(2), None));
+        assert_eq!(file.record("c"), record("c", None, None));
+
+        let content = toml::to_string(&file).unwrap();
+        assert_eq!(toml::from_str::<SplitsFile>(&content).unwrap().splits, file.splits);
+    }
+
+    #[test]
+    fn test_rewound() {
+        let mut run = Run::new(vec![record("a", None, None), record("b", None, None)]);
+        assert!(!run.rewound(0));
+
+        run.start(100_000);
+        run.split(110_000);
+        assert!(!run.rewound(110_000));
+        assert!(run.rewound(105_000));
+        assert!(run.rewound(50_000));
+
+        // Splitting on a rewound IGT doesn't underflow the segment.
+        run.split(105_000);
+        assert_eq!(run.records[1].gold, Some(0));
+        assert!(!run.rewound(0));
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(-1_500), "-00:00:01.50");
+        assert_eq!(format_delta(61_000), "+00:01:01.00");
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use hudhook::tracing::error;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{begin_popup, close_button, load_file, popup_anchor, write_file};
+use crate::remote::Control;
+use crate::util::{get_dll_relative_path, write_text};
+
+const POPUP_TAG: &str = "##stats";
+
+/// Whether a stats widget is configured, so events aren't queued for nobody.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Events reported by other widgets, drained by the stats widget.
+static EVENTS: Mutex<Vec<StatEvent>> = Mutex::new(Vec::new());
+
+/// All-time deaths and attempts at the last boss attempted, for text outputs.
+static DEATHS: AtomicU32 = AtomicU32::new(0);
+static ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+
+pub(crate) fn deaths() -> u32 {
+    DEATHS.load(Ordering::Relaxed)
+}
+
+pub(crate) fn attempts() -> u32 {
+    ATTEMPTS.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StatEvent {
+    Death,
+    Quitout,
+    Warp,
+    BossAttempt(String),
+}
+
+/// Reports an event the stats widget can't see by itself, such as the start
+/// of a boss attempt.
+pub(crate) fn record(event: StatEvent) {
+    if ENABLED.load(Ordering::Relaxed) {
+        if let Ok(mut events) = EVENTS.lock() {
+            events.push(event);
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Counters {
+    deaths: u32,
+    quitouts: u32,
+    warps: u32,
+    boss_attempts: u32,
+}
+
+impl Counters {
+    fn add(&mut self, event: &StatEvent) {
+        match event {
+            StatEvent::Death => self.deaths += 1,
+            StatEvent::Quitout => self.quitouts += 1,
+            StatEvent::Warp => self.warps += 1,
+            StatEvent::BossAttempt(_) => self.boss_attempts += 1,
+        }
+    }
+}
+
+/// Counters in total, by map and by boss.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct Stats {
+    #[serde(default)]
+    total: Counters,
+    #[serde(default)]
+    areas: BTreeMap<String, Counters>,
+    #[serde(default)]
+    bosses: BTreeMap<String, u32>,
+}
+
+impl Stats {
+    fn add(&mut self, event: &StatEvent, area: Option<MapId>) {
+        self.total.add(event);
+        if let Some(area) = area {
+            self.areas.entry(area.to_string()).or_default().add(event);
+        }
+        if let StatEvent::BossAttempt(boss) = event {
+            *self.bosses.entry(boss.clone()).or_default() += 1;
+        }
+    }
+}
+
+/// Turns HP, map and quitout readings into events.
+#[derive(Debug, Default)]
+struct Watcher {
+    hp: Option<u32>,
+    map_id: Option<MapId>,
+    quitout: bool,
+    /// The next load screen was caused by a death or a quitout.
+    expected_load: bool,
+}
+
+impl Watcher {
+    /// A death is HP dropping to 0, a quitout the quitout flag being raised.
+    /// Any other time the player stops being readable, they're loading into
+    /// a new area, which counts as a warp.
+    fn observe(&mut self, hp: Option<u32>, map_id: Option<MapId>, quitout: bool) -> Vec<StatEvent> {
+        let mut events = Vec::new();
+
+        if quitout && !self.quitout {
+            events.push(StatEvent::Quitout);
+            self.expected_load = true;
+        }
+
+        match (self.hp, hp) {
+            (Some(prev), Some(0)) if prev > 0 => {
+                events.push(StatEvent::Death);
+                self.expected_load = true;
+            },
+            (Some(_), None) => {
+                if !self.expected_load {
+                    events.push(StatEvent::Warp);
+                }
+                self.expected_load = false;
+            },
+            _ => {},
+        }
+
+        self.hp = hp;
+        self.quitout = quitout;
+        self.map_id = map_id.or(self.map_id);
+        events
+    }
+}
+
+/// Counts deaths, quitouts, warps and boss attempts, for the session and
+/// across sessions.
+pub(crate) struct StatsTracker {
+    label_close: String,
+    hotkey_close: Key,
+
+    hp: PointerChain<[u32; 3]>,
+    global_position: Position,
+    quitout: PointerChain<u8>,
+
+    watcher: Watcher,
+    session: Stats,
+    all_time: Stats,
+    last_boss: Option<String>,
+
+    path: Option<PathBuf>,
+    text_files: Option<PathBuf>,
+
+    logs: Vec<String>,
+}
+
+impl StatsTracker {
+    pub(crate) fn new(
+        hp: PointerChain<[u32; 3]>,
+        global_position: Position,
+        quitout: PointerChain<u8>,
+        text_files: bool,
+        hotkey_close: Key,
+    ) -> Self {
+        ENABLED.store(true, Ordering::Relaxed);
+
+        let path = get_dll_relative_path("stats.toml");
+
+        let mut logs = Vec::new();
+        let all_time = load_file::<Stats>(path.as_deref(), &mut logs);
+
+        let mut stats = StatsTracker {
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            hp,
+            global_position,
+            quitout,
+            watcher: Watcher::default(),
+            session: Stats::default(),
+            all_time,
+            last_boss: None,
+            path,
+            text_files: get_dll_relative_path("obs").filter(|_| text_files),
+            logs,
+        };
+        stats.publish();
+        stats.write_text_files();
+        stats
+    }
+
+    fn add(&mut self, events: Vec<StatEvent>) {
+        if events.is_empty() {
+            return;
+        }
+
+        let area = self.watcher.map_id;
+        for event in &events {
+            self.session.add(event, area);
+            self.all_time.add(event, area);
+            if let StatEvent::BossAttempt(boss) = event {
+                self.last_boss = Some(boss.clone());
+            }
+        }
+
+        self.publish();
+        self.write_file();
+        self.write_text_files();
+    }
+
+    fn publish(&self) {
+        let attempts = self.last_boss.as_ref().and_then(|boss| self.all_time.bosses.get(boss));
+        DEATHS.store(self.all_time.total.deaths, Ordering::Relaxed);
+        ATTEMPTS.store(attempts.copied().unwrap_or(0), Ordering::Relaxed);
+    }
+
+    fn write_file(&mut self) {
+        write_file(self.path.as_deref(), &self.all_time, &mut self.logs);
+    }
+
+    /// One file per counter, for OBS text sources to read from.
+    fn text_file_contents(&self) -> Vec<(&'static str, String)> {
+        let area = self.watcher.map_id.map(|m| m.to_string()).unwrap_or_default();
+        let area_deaths = self.all_time.areas.get(&area).map(|c| c.deaths).unwrap_or(0);
+        let boss_attempts = self
+            .last_boss
+            .as_ref()
+            .map(|boss| format!("{boss}: {}", self.all_time.bosses.get(boss).unwrap_or(&0)))
+            .unwrap_or_default();
+
+        vec![
+            ("deaths.txt", self.all_time.total.deaths.to_string()),
+            ("session_deaths.txt", self.session.total.deaths.to_string()),
+            ("area_deaths.txt", area_deaths.to_string()),
+            ("quitouts.txt", self.all_time.total.quitouts.to_string()),
+            ("warps.txt", self.all_time.total.warps.to_string()),
+            ("boss_attempts.txt", boss_attempts),
+        ]
+    }
+
+    fn write_text_files(&mut self) {
+        let Some(dir) = self.text_files.as_ref() else {
+            return;
+        };
+
+        let result = self
+            .text_file_contents()
+            .into_iter()
+            .try_for_each(|(name, content)| write_text(&dir.join(name), &content));
+
+        if let Err(e) = result {
+            error!("{e}");
+            self.logs.push(e);
+            self.text_files = None;
+        }
+    }
+
+    fn reset_session(&mut self) {
+        self.session = Stats::default();
+        self.write_text_files();
+    }
+
+    fn render_counters(ui: &imgui::Ui, label: &str, counters: &Counters) {
+        ui.text(format!(
+            "{label}: {} deaths, {} quitouts, {} warps, {} boss attempts",
+            counters.deaths, counters.quitouts, counters.warps, counters.boss_attempts
+        ));
+    }
+}
+
+impl Widget for StatsTracker {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size("Stats", [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            Self::render_counters(ui, "Session", &self.session.total);
+            Self::render_counters(ui, "All time", &self.all_time.total);
+
+            if let Some(area) = self.watcher.map_id {
+                let counters = self.all_time.areas.get(&area.to_string()).copied();
+                Self::render_counters(ui, &area.to_string(), &counters.unwrap_or_default());
+            }
+
+            ui.separator();
+            ui.child_window("##stats-bosses").size([400., 150.]).build(|| {
+                for (boss, attempts) in &self.all_time.bosses {
+                    ui.text(format!("{boss}: {attempts}"));
+                }
+            });
+
+            if ui.button_with_size("Reset session", [400., button_height]) {
+                self.reset_session();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn interact(&mut self, _ui: &imgui::Ui) {
+        let hp = self.hp.read().map(|[hp, ..]| hp);
+        let map_id = self.global_position.read_map_id().map(MapId);
+        let quitout = self.quitout.read().map(|q| q != 0).unwrap_or(false);
+
+        let mut events = self.watcher.observe(hp, map_id, quitout);
+        if let Ok(mut reported) = EVENTS.lock() {
+            events.append(&mut reported);
+        }
+        self.add(events);
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+impl Control for StatsTracker {
+    fn label(&self) -> String {
+        "Stats".to_string()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["reset_session"]
+    }
+
+    fn run(&mut self, _: &str, _: &Value) -> Result<(), String> {
+        self.reset_session();
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({ "session": self.session.total, "all_time": self.all_time.total })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watcher() {
+        let limgrave = Some(MapId::new(60, 42, 36, 0));
+        let mut watcher = Watcher::default();
+
+        assert!(watcher.observe(None, None, false).is_empty());
+        assert!(watcher.observe(Some(500), limgrave, false).is_empty());
+        assert_eq!(watcher.observe(Some(0), limgrave, false), [StatEvent::Death]);
+        assert!(watcher.observe(Some(0), limgrave, false).is_empty());
+        // Reloading after a death isn't a warp.
+        assert!(watcher.observe(None, None, false).is_empty());
+        assert!(watcher.observe(Some(500), limgrave, false).is_empty());
+
+        assert_eq!(watcher.observe(None, None, false), [StatEvent::Warp]);
+        assert!(watcher.observe(Some(500), limgrave, false).is_empty());
+
+        assert_eq!(watcher.observe(Some(500), limgrave, true), [StatEvent::Quitout]);
+        assert!(watcher.observe(None, None, true).is_empty());
+        assert!(watcher.observe(None, None, false).is_empty());
+        assert_eq!(watcher.map_id, limgrave);
+    }
+
+    #[test]
+    fn test_stats() {
+        let stormveil = MapId::new(10, 0, 0, 0);
+        let mut stats = Stats::default();
+        stats.add(&StatEvent::Death, Some(stormveil));
+        stats.add(&StatEvent::Death, None);
+        stats.add(&StatEvent::BossAttempt("Margit, the Fell Omen".to_string()), Some(stormveil));
+
+        assert_eq!(stats.total, Counters { deaths: 2, boss_attempts: 1, ..Default::default() });
+        assert_eq!(stats.areas["m10_00_00_00"], Counters {
+            deaths: 1,
+            boss_attempts: 1,
+            ..Default::default()
+        });
+        assert_eq!(stats.bosses["Margit, the Fell Omen"], 1);
+
+        let content = toml::to_string(&stats).unwrap();
+        assert_eq!(toml::from_str::<Stats>(&content).unwrap(), stats);
+    }
+}
@@ -1,12 +1,34 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use imgui::{ProgressBar, StyleColor};
 use libeldenring::memedit::PointerChain;
 use libeldenring::pointer_chain;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::Widget;
+use serde_json::{json, Value};
 use windows::Win32::System::Memory::{
     VirtualAlloc, MEM_COMMIT, MEM_RESERVE, PAGE_EXECUTE_READWRITE,
 };
 
+use crate::remote::Control;
+
+/// Address of the entity the player is locked on to, written by the detour
+/// while the target widget is enabled.
+static TARGET_ENTITY: AtomicU64 = AtomicU64::new(0);
+
+/// The entity the player is locked on to, if the target widget is enabled.
+pub(crate) fn target_entity() -> Option<usize> {
+    match TARGET_ENTITY.load(Ordering::Relaxed) {
+        0 => None,
+        addr => Some(addr as usize),
+    }
+}
+
+/// HP, base max HP and max HP of an entity.
+pub(crate) fn entity_hp(entity_addr: usize) -> PointerChain<[u32; 3]> {
+    pointer_chain!(entity_addr + 0x190, 0, 0x138)
+}
+
 #[derive(Debug, Default)]
 struct EnemyInfo {
     hp: u32,
@@ -63,7 +85,6 @@ pub(crate) struct Target {
     detour_orig_data: [u8; 11],
     hotkey: Option<Key>,
     is_enabled: bool,
-    entity_addr: u64,
 }
 
 unsafe impl Send for Target {}
@@ -99,21 +120,21 @@ impl Target {
             detour_orig_data: Default::default(),
             hotkey,
             is_enabled: false,
-            entity_addr: 0,
         }
     }
 
     fn get_data(&self) -> Option<EnemyInfo> {
-        if !self.is_enabled || self.entity_addr == 0 {
+        if !self.is_enabled {
             return None;
         }
+        let entity_addr = target_entity()?;
 
         let epc = EntityPointerChains {
-            hp: pointer_chain!(self.entity_addr as usize + 0x190, 0, 0x138),
-            sp: pointer_chain!(self.entity_addr as usize + 0x190, 0, 0x154),
-            mp: pointer_chain!(self.entity_addr as usize + 0x190, 0, 0x148),
-            res: pointer_chain!(self.entity_addr as usize + 0x190, 0x20, 0x10),
-            poise: pointer_chain!(self.entity_addr as usize + 0x190, 0x40, 0x10),
+            hp: entity_hp(entity_addr),
+            sp: pointer_chain!(entity_addr + 0x190, 0, 0x154),
+            mp: pointer_chain!(entity_addr + 0x190, 0, 0x148),
+            res: pointer_chain!(entity_addr + 0x190, 0x20, 0x10),
+            poise: pointer_chain!(entity_addr + 0x190, 0x40, 0x10),
         };
 
         let [hp, _, max_hp] = epc.hp.read()?;
@@ -133,7 +154,7 @@ impl Target {
         let detour_addr = self.detour_addr.eval().unwrap();
         let alloc_addr = self.alloc_addr.eval().unwrap();
 
-        let data_ptr = (&self.entity_addr as *const u64) as usize;
+        let data_ptr = TARGET_ENTITY.as_ptr() as usize;
         let going_jmp_to = (alloc_addr as isize - detour_addr as isize - 5) as i32;
         let returning_jmp_to = (detour_addr as isize - alloc_addr as isize - 11) as i32;
 
@@ -159,6 +180,7 @@ impl Target {
     fn disable(&mut self) {
         self.detour_addr.write(self.detour_orig_data);
         self.is_enabled = false;
+        TARGET_ENTITY.store(0, Ordering::Relaxed);
     }
 }
 
@@ -305,3 +327,33 @@ impl Widget for Target {
         }
     }
 }
+
+impl Control for Target {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["toggle", "on", "off"]
+    }
+
+    fn run(&mut self, action: &str, _: &Value) -> Result<(), String> {
+        let enabled = match action {
+            "on" => true,
+            "off" => false,
+            _ => !self.is_enabled,
+        };
+
+        // Enabling twice would save the detour itself as the original code.
+        match (enabled, self.is_enabled) {
+            (true, false) => self.enable(),
+            (false, true) => self.disable(),
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Value {
+        json!({ "enabled": self.is_enabled })
+    }
+}
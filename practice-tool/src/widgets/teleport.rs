@@ -0,0 +1,308 @@
+use imgui::InputText;
+use libeldenring::prelude::{MapId, Position as ErPosition};
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use serde_json::{json, Value};
+
+use super::position::teleport;
+use super::warp::LocationWarp;
+use super::{begin_popup, close_button, popup_anchor};
+use crate::remote::{arg, Control};
+
+const POPUP_TAG: &str = "##teleport";
+
+/// Coordinates as typed or pasted by the user.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Coordinates {
+    pub(crate) map_id: Option<MapId>,
+    pub(crate) position: [f32; 3],
+    pub(crate) facing: Option<f32>,
+}
+
+/// Parses coordinates in the format printed by the position indicator, e.g.
+/// `m60_42_36_00 123.45 67.89 10.00`. The map id is optional, and an optional
+/// fourth number is taken as the facing angle. Commas are accepted as
+/// separators.
+pub(crate) fn parse_coordinates(s: &str) -> Result<Coordinates, String> {
+    let mut tokens =
+        s.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()).peekable();
+
+    let map_id = match tokens.peek() {
+        Some(t) if t.starts_with(['m', 'M']) => {
+            let map_id = t.parse::<MapId>()?;
+            tokens.next();
+            Some(map_id)
+        },
+        _ => None,
+    };
+
+    let numbers = tokens
+        .map(|t| t.parse::<f32>().map_err(|e| format!("Invalid coordinate \"{t}\": {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match numbers.as_slice() {
+        [x, y, z] => Ok(Coordinates { map_id, position: [*x, *y, *z], facing: None }),
+        [x, y, z, a] => Ok(Coordinates { map_id, position: [*x, *y, *z], facing: Some(*a) }),
+        _ => Err(format!("Expected 3 or 4 coordinates, found {}", numbers.len())),
+    }
+}
+
+/// Teleports to coordinates entered by hand.
+pub(crate) struct Teleport {
+    label: String,
+    label_close: String,
+    hotkey: Option<Key>,
+    hotkey_close: Key,
+
+    global_position: ErPosition,
+    chunk_position: ErPosition,
+    torrent_chunk_position: ErPosition,
+    warp: LocationWarp,
+
+    input: String,
+    map_id: String,
+    position: [f32; 3],
+    facing: f32,
+    set_facing: bool,
+    error: Option<String>,
+}
+
+impl Teleport {
+    pub(crate) fn new(
+        global_position: ErPosition,
+        chunk_position: ErPosition,
+        torrent_chunk_position: ErPosition,
+        warp: LocationWarp,
+        hotkey: Option<Key>,
+        hotkey_close: Key,
+    ) -> Self {
+        let label = match &hotkey {
+            Some(k) => format!("Teleport to coordinates ({k})"),
+            None => "Teleport to coordinates".to_string(),
+        };
+        let label_close = format!("Close ({hotkey_close})");
+
+        Teleport {
+            label,
+            label_close,
+            hotkey,
+            hotkey_close,
+            global_position,
+            chunk_position,
+            torrent_chunk_position,
+            warp,
+            input: String::new(),
+            map_id: String::new(),
+            position: [0.; 3],
+            facing: 0.,
+            set_facing: false,
+            error: None,
+        }
+    }
+
+    fn apply_input(&mut self) {
+        match parse_coordinates(&self.input) {
+            Ok(Coordinates { map_id, position, facing }) => {
+                self.map_id = map_id.map(|m| m.to_string()).unwrap_or_default();
+                self.position = position;
+                self.set_facing = facing.is_some();
+                if let Some(facing) = facing {
+                    self.facing = facing;
+                }
+                self.error = None;
+            },
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn read_current(&mut self) {
+        if let (Some([x, y, z, _, _]), Some(facing), Some(map_id)) = (
+            self.global_position.read(),
+            self.chunk_position.angle1.read(),
+            self.global_position.read_map_id(),
+        ) {
+            self.map_id = MapId(map_id).to_string();
+            self.position = [x, y, z];
+            self.facing = facing;
+        }
+    }
+
+    fn teleport(&mut self) {
+        let map_id = match self.map_id.trim() {
+            "" => None,
+            map_id => match map_id.parse::<MapId>() {
+                Ok(map_id) => Some(map_id),
+                Err(e) => {
+                    self.error = Some(e);
+                    return;
+                },
+            },
+        };
+
+        let (Some([_, _, _, r1, r2]), Some(current_map_id)) =
+            (self.chunk_position.read(), self.global_position.read_map_id())
+        else {
+            return;
+        };
+
+        let [x, y, z] = self.position;
+        let position = [x, y, z, if self.set_facing { self.facing } else { r1 }, r2];
+
+        match map_id {
+            Some(map_id) if !MapId(current_map_id).same_map(&map_id) => {
+                self.warp.warp_to(map_id, position)
+            },
+            map_id => teleport(
+                &self.global_position,
+                &self.chunk_position,
+                &self.torrent_chunk_position,
+                map_id.map(|m| m.0).unwrap_or(current_map_id),
+                position,
+            ),
+        }
+    }
+}
+
+impl Widget for Teleport {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let anchor = popup_anchor();
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        if let Some(_token) = begin_popup(ui, POPUP_TAG, anchor) {
+            let _tok = ui.push_item_width(300.);
+
+            if InputText::new(ui, "Paste", &mut self.input)
+                .hint("m60_42_36_00 123.45 67.89 10.00")
+                .build()
+            {
+                self.apply_input();
+            }
+
+            InputText::new(ui, "Map", &mut self.map_id).hint("Current map").build();
+            ui.input_float3("Position", &mut self.position).build();
+            ui.checkbox("Set facing", &mut self.set_facing);
+            if self.set_facing {
+                ui.input_float("Facing", &mut self.facing).build();
+            }
+
+            if let Some(e) = self.error.as_ref() {
+                ui.text_colored([1., 0.3, 0.3, 1.], e);
+            }
+
+            if ui.button_with_size("Current position", [400., button_height]) {
+                self.read_current();
+            }
+
+            if ui.button_with_size("Teleport", [400., button_height]) {
+                self.teleport();
+            }
+
+            close_button(ui, &self.label_close, self.hotkey_close, 400.);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.warp.update();
+
+        if ui.is_any_item_active() {
+            return;
+        }
+
+        if self.hotkey.as_ref().map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.teleport();
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.warp.log(&tx);
+    }
+}
+
+/// `teleport` takes optional coordinates in the paste field's format:
+/// `{ "coordinates": "m60_42_36_00 123.45 67.89 10.00" }`.
+impl Control for Teleport {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn actions(&self) -> &'static [&'static str] {
+        &["teleport", "read_current"]
+    }
+
+    fn run(&mut self, action: &str, args: &Value) -> Result<(), String> {
+        if action == "read_current" {
+            self.read_current();
+            return Ok(());
+        }
+
+        self.error = None;
+        if let Some(coordinates) = arg::<Option<String>>(args, "coordinates")? {
+            self.input = coordinates;
+            self.apply_input();
+        }
+        if self.error.is_none() {
+            self.teleport();
+        }
+
+        match self.error.clone() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn state(&mut self) -> Value {
+        json!({
+            "map_id": self.map_id,
+            "position": self.position,
+            "facing": self.set_facing.then_some(self.facing),
+            "pending": self.warp.is_pending(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_eq!(
+            parse_coordinates("m60_42_36_00 123.45 67.89 10.00"),
+            Ok(Coordinates {
+                map_id: Some(MapId::new(60, 42, 36, 0)),
+                position: [123.45, 67.89, 10.],
+                facing: None,
+            })
+        );
+        assert_eq!(
+            parse_coordinates("  1.5, -2,3  0.75 "),
+            Ok(Coordinates { map_id: None, position: [1.5, -2., 3.], facing: Some(0.75) })
+        );
+        assert_eq!(
+            parse_coordinates("M10_00_00_00\t-1 -2 -3"),
+            Ok(Coordinates {
+                map_id: Some(MapId::new(10, 0, 0, 0)),
+                position: [-1., -2., -3.],
+                facing: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_coordinates_errors() {
+        assert!(parse_coordinates("").is_err());
+        assert!(parse_coordinates("1 2").is_err());
+        assert!(parse_coordinates("1 2 3 4 5").is_err());
+        assert!(parse_coordinates("1 2 x").is_err());
+        assert!(parse_coordinates("m60_42_36 1 2 3").is_err());
+        assert!(parse_coordinates("m60_42_36_300 1 2 3").is_err());
+    }
+}
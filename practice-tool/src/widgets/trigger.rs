@@ -0,0 +1,213 @@
+use libeldenring::prelude::*;
+use serde::Deserialize;
+
+use super::boss_reset::boss_flag;
+
+const DEFAULT_RADIUS: f32 = 5.0;
+
+/// Config fields describing a trigger. At most one of `event_flag`, `boss`
+/// and `map_id` can be set; `position` and `radius` narrow `map_id` down to
+/// a region of the map.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub(crate) struct TriggerSpec {
+    pub(crate) event_flag: Option<u32>,
+    pub(crate) boss: Option<String>,
+    pub(crate) map_id: Option<MapId>,
+    pub(crate) position: Option<[f32; 3]>,
+    pub(crate) radius: Option<f32>,
+}
+
+/// A condition that timers wait for.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Trigger {
+    /// The event flag goes from off to on.
+    EventFlag(u32),
+    /// The boss's defeated flag, looked up by name in the boss table.
+    Boss(String),
+    /// The player enters the map.
+    MapId(MapId),
+    /// The player gets within `radius` of `position` on the map.
+    Region { map_id: MapId, position: [f32; 3], radius: f32 },
+    /// Only the hotkey.
+    Manual,
+}
+
+impl TryFrom<TriggerSpec> for Trigger {
+    type Error = String;
+
+    fn try_from(spec: TriggerSpec) -> Result<Self, Self::Error> {
+        match (spec.event_flag, spec.boss, spec.map_id, spec.position) {
+            (Some(flag), None, None, None) => Ok(Trigger::EventFlag(flag)),
+            (None, Some(boss), None, None) => Ok(Trigger::Boss(boss)),
+            (None, None, Some(map_id), None) => Ok(Trigger::MapId(map_id)),
+            (None, None, Some(map_id), Some(position)) => Ok(Trigger::Region {
+                map_id,
+                position,
+                radius: spec.radius.unwrap_or(DEFAULT_RADIUS),
+            }),
+            (None, None, None, None) => Ok(Trigger::Manual),
+            (None, None, None, Some(_)) => Err("A position needs a map_id".to_string()),
+            _ => Err("Only one of event_flag, boss and map_id can be set".to_string()),
+        }
+    }
+}
+
+impl Trigger {
+    /// Replaces a boss name with the boss's defeated flag.
+    pub(crate) fn resolve(self) -> Result<Trigger, String> {
+        match self {
+            Trigger::Boss(boss) => {
+                boss_flag(&boss).map(Trigger::EventFlag).ok_or(format!("Unknown boss \"{boss}\""))
+            },
+            trigger => Ok(trigger),
+        }
+    }
+
+    /// Reads what the trigger depends on.
+    pub(crate) fn read(&self, event_flags: &EventFlags, global_position: &Position) -> Reading {
+        let flag = match self {
+            Trigger::EventFlag(flag) => event_flags.get_flag(*flag),
+            _ => None,
+        };
+        let map_id = global_position.read_map_id().map(MapId);
+        let position = global_position.read().map(|[x, y, z, ..]| [x, y, z]);
+
+        Reading { flag, map_id, position }
+    }
+}
+
+/// Game state a trigger is checked against.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Reading {
+    pub(crate) flag: Option<bool>,
+    pub(crate) map_id: Option<MapId>,
+    pub(crate) position: Option<[f32; 3]>,
+}
+
+/// Last observed state of a trigger, to fire on transitions only.
+#[derive(Debug, Default)]
+pub(crate) struct Watch {
+    flag: Option<bool>,
+    map_id: Option<MapId>,
+    inside: Option<bool>,
+}
+
+impl Watch {
+    /// Records the latest reading and tells whether the trigger fired since
+    /// the previous one. Values that couldn't be read keep their last state.
+    pub(crate) fn observe(&mut self, trigger: &Trigger, reading: Reading) -> bool {
+        let inside = match (trigger, reading.map_id, reading.position) {
+            (Trigger::Region { map_id, position, radius }, Some(cur), Some(pos)) => {
+                let distance =
+                    pos.iter().zip(position).map(|(a, b)| (a - b) * (a - b)).sum::<f32>();
+                Some(cur.same_map(map_id) && distance <= radius * radius)
+            },
+            _ => None,
+        };
+
+        let fired = match trigger {
+            Trigger::EventFlag(_) => self.flag == Some(false) && reading.flag == Some(true),
+            Trigger::MapId(target) => match (self.map_id, reading.map_id) {
+                (Some(prev), Some(cur)) => cur.same_map(target) && !prev.same_map(target),
+                _ => false,
+            },
+            Trigger::Region { .. } => self.inside == Some(false) && inside == Some(true),
+            Trigger::Boss(_) | Trigger::Manual => false,
+        };
+
+        self.flag = reading.flag.or(self.flag);
+        self.map_id = reading.map_id.or(self.map_id);
+        self.inside = inside.or(self.inside);
+        fired
+    }
+
+    /// Forgets the flag state, so a flag that's already on has to be turned
+    /// off and on again to fire.
+    pub(crate) fn clear_flag(&mut self) {
+        self.flag = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(flag: bool) -> Reading {
+        Reading { flag: Some(flag), ..Default::default() }
+    }
+
+    fn at(map_id: MapId, position: [f32; 3]) -> Reading {
+        Reading { flag: None, map_id: Some(map_id), position: Some(position) }
+    }
+
+    #[test]
+    fn test_trigger_spec() {
+        let stormveil = MapId::new(10, 0, 0, 0);
+        let spec = |event_flag, map_id, position| TriggerSpec {
+            event_flag,
+            map_id,
+            position,
+            ..Default::default()
+        };
+
+        assert_eq!(Trigger::try_from(spec(None, None, None)), Ok(Trigger::Manual));
+        assert_eq!(Trigger::try_from(spec(Some(1), None, None)), Ok(Trigger::EventFlag(1)));
+        assert_eq!(
+            Trigger::try_from(spec(None, Some(stormveil), None)),
+            Ok(Trigger::MapId(stormveil))
+        );
+        assert_eq!(
+            Trigger::try_from(spec(None, Some(stormveil), Some([1., 2., 3.]))),
+            Ok(Trigger::Region {
+                map_id: stormveil,
+                position: [1., 2., 3.],
+                radius: DEFAULT_RADIUS
+            })
+        );
+        assert!(Trigger::try_from(spec(None, None, Some([1., 2., 3.]))).is_err());
+        assert!(Trigger::try_from(spec(Some(1), Some(stormveil), None)).is_err());
+    }
+
+    #[test]
+    fn test_watch_flag() {
+        let trigger = Trigger::EventFlag(10000800);
+
+        let mut watch = Watch::default();
+        assert!(!watch.observe(&trigger, flag(true)));
+
+        let mut watch = Watch::default();
+        assert!(!watch.observe(&trigger, flag(false)));
+        assert!(!watch.observe(&trigger, Reading::default()));
+        assert!(watch.observe(&trigger, flag(true)));
+        assert!(!watch.observe(&trigger, flag(true)));
+    }
+
+    #[test]
+    fn test_watch_map() {
+        let stormveil = MapId::new(10, 0, 0, 0);
+        let limgrave = MapId::new(60, 42, 36, 0);
+        let trigger = Trigger::MapId(stormveil);
+
+        let mut watch = Watch::default();
+        assert!(!watch.observe(&trigger, at(stormveil, [0.; 3])));
+        assert!(!watch.observe(&trigger, at(limgrave, [0.; 3])));
+        assert!(!watch.observe(&trigger, at(MapId::new(60, 43, 36, 0), [0.; 3])));
+        assert!(watch.observe(&trigger, at(stormveil, [0.; 3])));
+        assert!(!watch.observe(&trigger, at(stormveil, [0.; 3])));
+    }
+
+    #[test]
+    fn test_watch_region() {
+        let stormveil = MapId::new(10, 0, 0, 0);
+        let trigger = Trigger::Region { map_id: stormveil, position: [10., 0., 10.], radius: 2. };
+
+        let mut watch = Watch::default();
+        assert!(!watch.observe(&trigger, at(stormveil, [10., 0., 11.])));
+        assert!(!watch.observe(&trigger, at(stormveil, [0., 0., 0.])));
+        assert!(!watch.observe(&trigger, Reading::default()));
+        assert!(watch.observe(&trigger, at(stormveil, [11., 0., 11.])));
+        assert!(!watch.observe(&trigger, at(stormveil, [10., 0., 10.])));
+        assert!(!watch.observe(&trigger, at(MapId::new(11, 0, 0, 0), [0., 0., 0.])));
+        assert!(!watch.observe(&trigger, at(MapId::new(11, 0, 0, 0), [10., 0., 10.])));
+    }
+}